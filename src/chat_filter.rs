@@ -0,0 +1,404 @@
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use pumpkin::{
+    plugin::{
+        block::sign_change::SignChangeEvent, player::player_chat::PlayerChatEvent, Cancellable,
+        EventHandler,
+    },
+    server::Server,
+};
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::config;
+
+const BYPASS_PERMISSION: &str = "pumpkin-essentials:command.chatfilter.bypass";
+const NOTIFY_PERMISSION: &str = "pumpkin-essentials:command.chatfilter.notify";
+
+// What happens to a message matching a filter rule
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    // Replace the matching text with *
+    #[default]
+    Censor,
+    // Drop the message
+    Block,
+    // Drop the message and tell staff
+    BlockAndWarn,
+}
+
+// A banned word, or a regex when `regex` is set
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRule {
+    pub pattern: String,
+    pub regex: bool,
+    pub action: FilterAction,
+}
+
+pub enum FilterResult {
+    // The message, censored when a censor rule matched
+    Allowed(String),
+    Blocked { warn_staff: bool },
+}
+
+// A rule compiled once from the config
+struct CompiledRule {
+    matcher: RuleMatcher,
+    action: FilterAction,
+}
+
+// Words match whole normalized words, regexes match the message as written
+enum RuleMatcher {
+    Word(String),
+    Regex(Regex),
+}
+
+static COMPILED_RULES: OnceCell<Vec<CompiledRule>> = OnceCell::new();
+
+fn compiled_rules() -> &'static [CompiledRule] {
+    COMPILED_RULES.get_or_init(|| compile_rules(&config::get().chat_filter.rules))
+}
+
+fn compile_rules(rules: &[FilterRule]) -> Vec<CompiledRule> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let matcher = if rule.regex {
+                match Regex::new(&format!("(?i){}", rule.pattern)) {
+                    Ok(regex) => RuleMatcher::Regex(regex),
+                    Err(err) => {
+                        log::warn!("Invalid chat filter pattern '{}': {}", rule.pattern, err);
+                        return None;
+                    }
+                }
+            } else {
+                let word: String = normalize(&rule.pattern).into_iter().map(|word| word.text).collect();
+                if word.is_empty() {
+                    log::warn!("Chat filter word '{}' has no letters, ignoring it", rule.pattern);
+                    return None;
+                }
+                RuleMatcher::Word(word)
+            };
+            Some(CompiledRule { matcher, action: rule.action })
+        })
+        .collect()
+}
+
+// A word of a message with leetspeak undone. For every character it keeps the byte offset
+// in the original text, and whether it was a symbol read as a letter like the @ in "b@d"
+#[derive(Debug, Default)]
+struct NormalizedWord {
+    text: String,
+    offsets: Vec<usize>,
+    symbols: Vec<bool>,
+}
+
+// Split a message into lowercase words with leetspeak undone, so "B4d" reads "bad".
+// Spacing and any punctuation that isn't leetspeak separate words
+fn normalize(text: &str) -> Vec<NormalizedWord> {
+    let mut words = Vec::new();
+    let mut word = NormalizedWord::default();
+
+    for (offset, c) in text.char_indices() {
+        let mapped = match c.to_ascii_lowercase() {
+            '0' => 'o',
+            '1' | '!' | '|' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            '8' => 'b',
+            c if c.is_alphanumeric() => c,
+            _ => {
+                if !word.text.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+        };
+        for lower in mapped.to_lowercase() {
+            word.text.push(lower);
+            word.offsets.push(offset);
+            word.symbols.push(!c.is_alphanumeric());
+        }
+    }
+    if !word.text.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+// Text a word rule is compared with, and the first and last byte offset it covers in the message
+struct Candidate {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+// Every word, every word without the symbols at its ends so "bad!" reads "bad", and every
+// run of single letters joined so "b a d" and "b.@.d" read "bad"
+fn word_candidates(words: &[NormalizedWord]) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+
+    for word in words {
+        let chars: Vec<char> = word.text.chars().collect();
+        let last_index = chars.len() - 1;
+        candidates.push(Candidate {
+            text: word.text.clone(),
+            start: word.offsets[0],
+            end: word.offsets[last_index],
+        });
+
+        let first = word.symbols.iter().position(|symbol| !symbol);
+        let last = word.symbols.iter().rposition(|symbol| !symbol);
+        if let (Some(first), Some(last)) = (first, last) {
+            if first > 0 || last < last_index {
+                candidates.push(Candidate {
+                    text: chars[first..=last].iter().collect(),
+                    start: word.offsets[first],
+                    end: word.offsets[last],
+                });
+            }
+        }
+    }
+
+    let mut run_start = 0;
+    while run_start < words.len() {
+        let mut run_end = run_start;
+        while run_end < words.len() && words[run_end].offsets.len() == 1 {
+            run_end += 1;
+        }
+        for first in run_start..run_end {
+            for last in first + 1..run_end {
+                candidates.push(Candidate {
+                    text: words[first..=last].iter().map(|word| word.text.as_str()).collect(),
+                    start: words[first].offsets[0],
+                    end: words[last].offsets[0],
+                });
+            }
+        }
+        run_start = run_end + 1;
+    }
+
+    candidates
+}
+
+// Check a message against the filter rules
+pub fn filter_message(message: &str) -> FilterResult {
+    if !config::get().chat_filter.enabled {
+        return FilterResult::Allowed(message.to_string());
+    }
+
+    apply_rules(compiled_rules(), message)
+}
+
+fn apply_rules(rules: &[CompiledRule], message: &str) -> FilterResult {
+    let candidates = word_candidates(&normalize(message));
+    let mut censored_ranges = Vec::new();
+
+    for rule in rules {
+        let ranges: Vec<(usize, usize)> = match &rule.matcher {
+            RuleMatcher::Word(word) => candidates
+                .iter()
+                .filter(|candidate| candidate.text == *word)
+                .map(|candidate| (candidate.start, candidate.end))
+                .collect(),
+            RuleMatcher::Regex(regex) => regex
+                .find_iter(message)
+                .filter(|found| !found.is_empty())
+                .map(|found| (found.start(), found.end() - 1))
+                .collect(),
+        };
+        if ranges.is_empty() {
+            continue;
+        }
+
+        match rule.action {
+            FilterAction::Block => return FilterResult::Blocked { warn_staff: false },
+            FilterAction::BlockAndWarn => return FilterResult::Blocked { warn_staff: true },
+            FilterAction::Censor => censored_ranges.extend(ranges),
+        }
+    }
+
+    if censored_ranges.is_empty() {
+        return FilterResult::Allowed(message.to_string());
+    }
+
+    let censored = message
+        .char_indices()
+        .map(|(offset, c)| {
+            let inside = censored_ranges.iter().any(|(start, end)| offset >= *start && offset <= *end);
+            if inside && !c.is_whitespace() { '*' } else { c }
+        })
+        .collect();
+    FilterResult::Allowed(censored)
+}
+
+// Tell staff holding the notify permission about a blocked message
+pub async fn warn_staff(server: &Server, player_name: &str, source: &str, message: &str) {
+    let notice = format!("{} tried to send a filtered {}: {}", player_name, source, message);
+    log::info!("[CHATFILTER] {}", notice);
+
+    for staff in server.get_all_players().await {
+        if staff.has_permission(NOTIFY_PERMISSION).await {
+            staff
+                .send_system_message(&TextComponent::text(format!("[Staff] {}", notice)))
+                .await;
+        }
+    }
+}
+
+// Filter text written by a player, None when it is blocked. Players with the bypass
+// permission are never filtered
+pub async fn filter_player_text(
+    server: &Server,
+    player: &pumpkin::entity::player::Player,
+    source: &str,
+    text: &str,
+) -> Option<String> {
+    if player.has_permission(BYPASS_PERMISSION).await {
+        return Some(text.to_string());
+    }
+
+    match filter_message(text) {
+        FilterResult::Allowed(text) => Some(text),
+        FilterResult::Blocked { warn_staff: notify } => {
+            if notify {
+                warn_staff(server, &player.gameprofile.name, source, text).await;
+            }
+            player
+                .send_system_message(&TextComponent::text("Your message was blocked by the chat filter"))
+                .await;
+            None
+        }
+    }
+}
+
+// Event handler for PlayerChatEvent censoring or blocking filtered messages
+pub struct ChatFilterHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerChatEvent> for ChatFilterHandler {
+    async fn handle_blocking(&self, server: &Arc<Server>, event: &mut PlayerChatEvent) {
+        if event.cancelled() {
+            return;
+        }
+        match filter_player_text(server, &event.player, "chat message", &event.message).await {
+            Some(message) => event.message = message,
+            None => event.set_cancelled(true),
+        }
+    }
+}
+
+// Event handler for SignChangeEvent applying the filter to every line of a sign
+pub struct SignFilterHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<SignChangeEvent> for SignFilterHandler {
+    async fn handle_blocking(&self, server: &Arc<Server>, event: &mut SignChangeEvent) {
+        if event.cancelled() || event.player.has_permission(BYPASS_PERMISSION).await {
+            return;
+        }
+
+        // Check the lines together so a word split over two lines is still caught
+        let text = event.lines.join(" ");
+        if filter_player_text(server, &event.player, "sign", &text).await.is_none() {
+            event.set_cancelled(true);
+            return;
+        }
+
+        for line in event.lines.iter_mut() {
+            if let FilterResult::Allowed(filtered) = filter_message(line) {
+                *line = filtered;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, regex: bool, action: FilterAction) -> FilterRule {
+        FilterRule { pattern: pattern.to_string(), regex, action }
+    }
+
+    // The filtered message, None when it was blocked
+    fn filter(rules: &[FilterRule], message: &str) -> Option<String> {
+        match apply_rules(&compile_rules(rules), message) {
+            FilterResult::Allowed(text) => Some(text),
+            FilterResult::Blocked { .. } => None,
+        }
+    }
+
+    #[test]
+    fn normalize_undoes_leetspeak_and_splits_words() {
+        let words = normalize("H3ll0, w0rld");
+        let texts: Vec<&str> = words.iter().map(|word| word.text.as_str()).collect();
+        assert_eq!(texts, ["hello", "world"]);
+        assert_eq!(words[1].offsets, [7, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn normalize_keeps_byte_offsets_of_multibyte_characters() {
+        let words = normalize("é b@d");
+        assert_eq!(words[0].text, "é");
+        assert_eq!(words[0].offsets, [0]);
+        assert_eq!(words[1].text, "bad");
+        assert_eq!(words[1].offsets, [3, 4, 5]);
+        assert_eq!(words[1].symbols, [false, true, false]);
+    }
+
+    #[test]
+    fn words_only_match_whole_words() {
+        let rules = [rule("ass", false, FilterAction::Censor)];
+        assert_eq!(filter(&rules, "first class").as_deref(), Some("first class"));
+        assert_eq!(filter(&rules, "what an ass").as_deref(), Some("what an ***"));
+        assert_eq!(filter(&rules, "a$$").as_deref(), Some("***"));
+    }
+
+    #[test]
+    fn words_match_leetspeak_and_trailing_symbols() {
+        let rules = [rule("bad", false, FilterAction::Censor)];
+        assert_eq!(filter(&rules, "so B4D!").as_deref(), Some("so ***!"));
+        assert_eq!(filter(&rules, "badge").as_deref(), Some("badge"));
+    }
+
+    #[test]
+    fn words_match_spaced_out_letters() {
+        let rules = [rule("bad", false, FilterAction::Censor)];
+        assert_eq!(filter(&rules, "you are a b a d person").as_deref(), Some("you are a * * * person"));
+        assert_eq!(filter(&rules, "b.@.d").as_deref(), Some("*****"));
+    }
+
+    #[test]
+    fn block_rules_drop_the_message() {
+        let rules = [rule("bad", false, FilterAction::Block)];
+        assert_eq!(filter(&rules, "not b a d at all"), None);
+        assert_eq!(filter(&rules, "fine").as_deref(), Some("fine"));
+        assert!(matches!(
+            apply_rules(&compile_rules(&[rule("bad", false, FilterAction::BlockAndWarn)]), "bad"),
+            FilterResult::Blocked { warn_staff: true }
+        ));
+    }
+
+    #[test]
+    fn regexes_match_the_message_as_written() {
+        let rules = [rule("discord\\.gg/\\w+", true, FilterAction::Censor)];
+        assert_eq!(filter(&rules, "join Discord.gg/abc now").as_deref(), Some("join ************** now"));
+    }
+
+    #[test]
+    fn empty_words_and_invalid_regexes_are_skipped() {
+        assert!(compile_rules(&[rule("...", false, FilterAction::Block)]).is_empty());
+        assert!(compile_rules(&[rule("(", true, FilterAction::Block)]).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use pumpkin::{
+    entity::player::Player,
+    plugin::{player::player_chat::PlayerChatEvent, Cancellable, EventHandler},
+    server::Server,
+};
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::commands::ignore::chat_recipients;
+use crate::commands::nick::display_name;
+use crate::config;
+use crate::text_format::parse_codes;
+
+const COLOR_PERMISSION: &str = "pumpkin-essentials:command.chat.color";
+
+// Prefix and suffix for players holding pumpkin-essentials:command.chat.group.<name>
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatGroup {
+    pub name: String,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+// Permission node giving a chat group
+pub fn group_permission(group: &ChatGroup) -> String {
+    format!("{}:command.chat.group.{}", crate::PLUGIN_NAME, group.name)
+}
+
+// First configured group the player belongs to, groups are checked in order
+async fn player_group(player: &Player) -> Option<&'static ChatGroup> {
+    for group in &config::get().chat.groups {
+        if player.has_permission(&group_permission(group)).await {
+            return Some(group);
+        }
+    }
+    None
+}
+
+// Fill every placeholder except {message}
+async fn fill_placeholders(template: &str, player: &Player) -> String {
+    let group = player_group(player).await;
+    template
+        .replace("{prefix}", group.map_or("", |group| group.prefix.as_str()))
+        .replace("{suffix}", group.map_or("", |group| group.suffix.as_str()))
+        .replace("{group}", group.map_or("", |group| group.name.as_str()))
+        .replace("{displayname}", &display_name(player).await)
+        .replace("{name}", &player.gameprofile.name)
+        .replace("{world}", &crate::world_name(&player.world().await))
+}
+
+// Build the chat line for a message. Codes from the template, prefixes and nicknames are
+// always applied, codes in the message only for players with the color permission
+pub async fn format_chat(player: &Player, message: &str) -> TextComponent {
+    let template = &config::get().chat.format;
+    let message_component = if player.has_permission(COLOR_PERMISSION).await {
+        parse_codes(message)
+    } else {
+        TextComponent::text(message.to_string())
+    };
+
+    let (before, after) = template.split_once("{message}").unwrap_or((template.as_str(), ""));
+    TextComponent::text("")
+        .add_child(parse_codes(&fill_placeholders(before, player).await))
+        .add_child(message_component)
+        .add_child(parse_codes(&fill_placeholders(after, player).await))
+}
+
+// Event handler for PlayerChatEvent sending the formatted message to every online player who
+// doesn't ignore the sender. The recipients of the event are empty for normal chat, so they
+// can't be used. Runs after the mute and filter handlers
+pub struct ChatFormatHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerChatEvent> for ChatFormatHandler {
+    async fn handle_blocking(&self, server: &Arc<Server>, event: &mut PlayerChatEvent) {
+        if !config::get().chat.enabled || event.cancelled() {
+            return;
+        }
+
+        let chat_line = format_chat(&event.player, &event.message).await;
+        log::info!("[CHAT] {}: {}", event.player.gameprofile.name, event.message);
+        for recipient in chat_recipients(server, event.player.gameprofile.id).await {
+            recipient.send_system_message(&chat_line).await;
+        }
+
+        // The message was delivered with our format, skip the vanilla one
+        event.set_cancelled(true);
+    }
+}
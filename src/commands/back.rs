@@ -3,9 +3,7 @@ use pumpkin::{
     command::{
         args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::require,
         CommandExecutor, CommandSender,
     },
     plugin::{
@@ -23,13 +21,15 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 use pumpkin_api_macros::with_runtime;
 
+use super::targets::player_required;
+
 // Structure pour stocker la position de retour
 #[derive(Clone, Debug)]
 pub struct BackLocation {
     pub position: Vector3<f64>,
     pub yaw: f32,
     pub pitch: f32,
-    pub world_name: String, // Pour les mondes multiples (optionnel pour l'instant)
+    pub world_name: String, // World the player was in, see crate::world_name
 }
 
 // Global storage for player back locations
@@ -93,7 +93,7 @@ impl CommandExecutor for BackExecutor {
                 Ok(())
             }
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
@@ -106,13 +106,14 @@ pub struct BackLocationHandler;
 impl EventHandler<PlayerTeleportEvent> for BackLocationHandler {
     async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerTeleportEvent) {
         // Save the 'from' position as the back location
+        let world_name = crate::world_name(&event.player.world().await);
         let mut back_locations = PLAYER_BACK_LOCATIONS.lock().await;
         
         let back_location = BackLocation {
             position: event.from,
             yaw: event.player.living_entity.entity.yaw.load(),
             pitch: event.player.living_entity.entity.pitch.load(),
-            world_name: "overworld".to_string(), // Default world for now
+            world_name,
         };
 
         back_locations.insert(event.player.gameprofile.id, back_location);
@@ -131,9 +132,6 @@ pub async fn clear_back_location_for_player(player_uuid: Uuid) {
     back_locations.remove(&player_uuid);
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(BackExecutor)
-    )
+    CommandTree::new(NAMES, DESCRIPTION).execute(BackExecutor)
 }
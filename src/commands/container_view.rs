@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use pumpkin_data::screen::WindowType;
+use pumpkin_inventory::generic_container_screen_handler::{
+    create_generic_9x3, create_generic_9x5, GenericContainerScreenHandler,
+};
+use pumpkin_inventory::player::player_inventory::PlayerInventory;
+use pumpkin_inventory::screen_handler::{
+    InventoryPlayer, ScreenHandler, ScreenHandlerBehaviour, ScreenHandlerFactory,
+};
+use pumpkin_protocol::java::server::play::SlotActionType;
+use pumpkin_util::text::TextComponent;
+use pumpkin_world::inventory::Inventory;
+use pumpkin_world::item::ItemStack;
+use std::any::Any;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Opens a chest screen showing another inventory, used by /enderchest and /invsee
+pub struct ContainerViewFactory {
+    pub inventory: Arc<dyn Inventory>,
+    pub title: String,
+    pub editable: bool,
+}
+
+impl ContainerViewFactory {
+    // Without `editable` the viewer can look without taking or placing items
+    pub fn new(inventory: Arc<dyn Inventory>, title: String, editable: bool) -> Self {
+        ContainerViewFactory { inventory, title, editable }
+    }
+}
+
+#[async_trait]
+impl ScreenHandlerFactory for ContainerViewFactory {
+    async fn create_screen_handler(
+        &self,
+        sync_id: u8,
+        player_inventory: &Arc<PlayerInventory>,
+        _player: &dyn InventoryPlayer,
+    ) -> Option<Arc<Mutex<dyn ScreenHandler>>> {
+        // Ender chests fit in 3 rows, player inventories need 5
+        let handler = if self.inventory.size() <= 27 {
+            create_generic_9x3(sync_id, player_inventory, self.inventory.clone())
+        } else {
+            create_generic_9x5(sync_id, player_inventory, self.inventory.clone())
+        };
+
+        if self.editable {
+            Some(Arc::new(Mutex::new(handler)))
+        } else {
+            Some(Arc::new(Mutex::new(ReadOnlyScreenHandler {
+                inner: handler,
+                viewed_slots: self.inventory.size(),
+            })))
+        }
+    }
+
+    fn get_display_name(&self) -> TextComponent {
+        TextComponent::text(self.title.clone())
+    }
+}
+
+// Whether a click could move items into or out of the viewed inventory, whose slots come
+// first in the window. Shift-clicks and double-clicks reach every slot wherever they start
+fn touches_viewed_slots(slot_index: i32, action_type: SlotActionType, viewed_slots: usize) -> bool {
+    match action_type {
+        SlotActionType::QuickMove | SlotActionType::PickupAll => true,
+        // Clicks outside the window use slot -999
+        _ => slot_index >= 0 && (slot_index as usize) < viewed_slots,
+    }
+}
+
+// Chest screen for read-only views. Clicks, shift-clicks, drags and hotbar swaps touching
+// the viewed inventory are refused before the chest logic runs, and the client is resynced
+pub struct ReadOnlyScreenHandler {
+    inner: GenericContainerScreenHandler,
+    viewed_slots: usize,
+}
+
+#[async_trait]
+impl ScreenHandler for ReadOnlyScreenHandler {
+    fn window_type(&self) -> Option<WindowType> {
+        self.inner.window_type()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_behaviour(&self) -> &ScreenHandlerBehaviour {
+        self.inner.get_behaviour()
+    }
+
+    fn get_behaviour_mut(&mut self) -> &mut ScreenHandlerBehaviour {
+        self.inner.get_behaviour_mut()
+    }
+
+    async fn on_closed(&mut self, player: &dyn InventoryPlayer) {
+        self.inner.on_closed(player).await;
+    }
+
+    async fn quick_move(&mut self, _player: &dyn InventoryPlayer, _slot_index: i32) -> ItemStack {
+        ItemStack::EMPTY.clone()
+    }
+
+    async fn on_slot_click(
+        &mut self,
+        slot_index: i32,
+        button: i32,
+        action_type: SlotActionType,
+        player: &dyn InventoryPlayer,
+    ) {
+        if touches_viewed_slots(slot_index, action_type, self.viewed_slots) {
+            self.sync_state().await;
+            return;
+        }
+        self.internal_on_slot_click(slot_index, button, action_type, player).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDER_CHEST_SLOTS: usize = 27;
+
+    #[test]
+    fn shift_clicks_are_refused_from_both_sides() {
+        // A shift-click in the viewed chest would copy the item to the viewer, one in the
+        // viewer's inventory would push it into the chest
+        assert!(touches_viewed_slots(0, SlotActionType::QuickMove, ENDER_CHEST_SLOTS));
+        assert!(touches_viewed_slots(40, SlotActionType::QuickMove, ENDER_CHEST_SLOTS));
+    }
+
+    #[test]
+    fn clicks_on_viewed_slots_are_refused() {
+        for action_type in [
+            SlotActionType::Pickup,
+            SlotActionType::Swap,
+            SlotActionType::Throw,
+            SlotActionType::QuickCraft,
+        ] {
+            assert!(touches_viewed_slots(0, action_type, ENDER_CHEST_SLOTS));
+            assert!(touches_viewed_slots(26, action_type, ENDER_CHEST_SLOTS));
+        }
+        assert!(touches_viewed_slots(30, SlotActionType::PickupAll, ENDER_CHEST_SLOTS));
+    }
+
+    #[test]
+    fn the_viewer_keeps_using_their_own_inventory() {
+        assert!(!touches_viewed_slots(27, SlotActionType::Pickup, ENDER_CHEST_SLOTS));
+        assert!(!touches_viewed_slots(62, SlotActionType::Swap, ENDER_CHEST_SLOTS));
+        assert!(!touches_viewed_slots(-999, SlotActionType::QuickCraft, ENDER_CHEST_SLOTS));
+    }
+}
@@ -3,9 +3,8 @@ use pumpkin::{
     command::{
         args::{Arg, ConsumedArgs, simple::SimpleArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -13,6 +12,7 @@ use pumpkin::{
 use pumpkin::command::CommandSender::Player;
 
 use super::home_common::{PLAYER_HOMES, ARG_HOME_NAME};
+use super::targets::player_required;
 
 const NAMES: [&str; 1] = ["delhome"];
 const DESCRIPTION: &str = "Delete one of your homes.";
@@ -61,15 +61,13 @@ impl CommandExecutor for DelhomeExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
     CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_HOME_NAME, SimpleArgConsumer).execute(DelhomeExecutor))
+        argument(ARG_HOME_NAME, SimpleArgConsumer).execute(DelhomeExecutor)
     )
 } use pumpkin_util::text::TextComponent;
@@ -1,11 +1,11 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::{Arg, ConsumedArgs},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -13,9 +13,13 @@ use pumpkin::{
 use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
 
+use super::container_view::ContainerViewFactory;
+use super::targets::{authorize_targets, is_self, player_required, PlayersOrNickArgumentConsumer};
+
 const NAMES: [&str; 1] = ["enderchest"];
 const DESCRIPTION: &str = "Open an enderchest for yourself or another player.";
 const ARG_TARGET: &str = "target";
+const MODIFY_PERMISSION: &str = "pumpkin-essentials:command.enderchest.modify";
 
 struct EnderchestExecutor;
 
@@ -27,37 +31,59 @@ impl CommandExecutor for EnderchestExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
+        let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
+            if players.len() == 1 {
+                players[0].clone()
+            } else {
+                return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+            }
+        } else if let Player(target) = sender {
+            target.clone()
+        } else {
+            return Err(player_required());
+        };
+
+        if authorize_targets(sender, vec![target_player.clone()], "enderchest").await.is_none() {
+            return Ok(());
+        }
+
         if let Player(target) = sender {
-            let _target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
+            // Viewing another player's chest is read-only without the modify permission.
+            // The screen works on the target's own ender chest inventory, so edits are
+            // saved with their player data
+            let is_own = is_self(sender, &target_player);
+            let editable = is_own || target.has_permission(MODIFY_PERMISSION).await;
+            let title = if is_own {
+                "Ender Chest".to_string()
             } else {
-                target.clone()
+                format!("{}'s Ender Chest", target_player.gameprofile.name)
             };
 
-            // TODO: Implement enderchest functionality
-            // This would require access to the player's enderchest inventory
-            // For now, we'll show a placeholder message
-            
-            target
-                .send_system_message(&TextComponent::text("Enderchest functionality is not yet implemented."))
-                .await;
+            let factory = ContainerViewFactory::new(
+                target_player.ender_chest_inventory(),
+                title,
+                editable,
+            );
+            target.open_handled_screen(&factory).await;
+
+            if !editable {
+                target
+                    .send_system_message(&TextComponent::text(format!(
+                        "Viewing {}'s ender chest in read-only mode",
+                        target_player.gameprofile.name
+                    )))
+                    .await;
+            }
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(EnderchestExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(EnderchestExecutor))
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(EnderchestExecutor)
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(EnderchestExecutor))
+}
@@ -1,18 +1,20 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
-use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
 
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer,
+};
+
 const NAMES: [&str; 1] = ["feed"];
 const DESCRIPTION: &str = "Feed yourself or another player.";
 const ARG_TARGET: &str = "target";
@@ -27,53 +29,40 @@ impl CommandExecutor for FeedExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
-            } else {
-                target.clone()
-            };
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "feed",
+        ).await else {
+            return Ok(());
+        };
 
+        for target_player in &target_players {
             // Set player's food level to maximum (20) and saturation to 5.0
             target_player.hunger_manager.level.store(20);
             target_player.hunger_manager.saturation.store(5.0);
             target_player.send_health().await;
 
-            let player_name = &target_player.gameprofile.name;
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text("You have been fed!"))
-                    .await;
-            } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "Fed {}",
-                        player_name
-                    )))
-                    .await;
-                    
-                target_player
-                    .send_system_message(&TextComponent::text("You have been fed!"))
-                    .await;
-            }
+            target_player
+                .send_system_message(&TextComponent::text("You have been fed!"))
+                .await;
+        }
 
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Fed {}",
+                    describe_targets(&target_players)
+                )))
+                .await;
         }
+
+        Ok(())
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(FeedExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(FeedExecutor))
-    )
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(FeedExecutor)
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(FeedExecutor))
 }
\ No newline at end of file
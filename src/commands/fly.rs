@@ -1,18 +1,20 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
-use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
-use crate::{get_fly_state, set_fly_state};
+use crate::player_state::{gamemode_grants_flight, get_fly_state, set_fly_state};
+
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer,
+};
 
 const NAMES: [&str; 1] = ["fly"];
 const DESCRIPTION: &str = "Toggle flight mode for yourself or another player.";
@@ -28,79 +30,89 @@ impl CommandExecutor for FlyExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "fly",
+        ).await else {
+            return Ok(());
+        };
+        let mut enabled_count = 0;
+
+        for target_player in &target_players {
+            let new_fly_state = toggle_fly(target_player).await;
+            if new_fly_state {
+                enabled_count += 1;
+            }
+
+            target_player
+                .send_system_message(&TextComponent::text(format!(
+                    "Flight mode {}",
+                    if new_fly_state { "enabled" } else { "disabled" }
+                )))
+                .await;
+        }
+
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            let message = if target_players.len() == 1 {
+                format!(
+                    "{} flight mode for {}",
+                    if enabled_count == 1 { "Enabled" } else { "Disabled" },
+                    describe_targets(&target_players)
+                )
             } else {
-                target.clone()
+                format!(
+                    "Toggled flight mode for {} (enabled for {}, disabled for {})",
+                    describe_targets(&target_players),
+                    enabled_count,
+                    target_players.len() - enabled_count
+                )
             };
+            sender
+                .send_message(TextComponent::text(message))
+                .await;
+        }
 
-            // Get current fly state from HashMap
-            let player_uuid = target_player.gameprofile.id;
-            let is_fly_enabled = get_fly_state(player_uuid).await;
-            
-            // Toggle fly state
-            let new_fly_state = !is_fly_enabled;
-            set_fly_state(player_uuid, new_fly_state).await;
+        Ok(())
+    }
+}
 
-            // Apply the fly state to the player's abilities
-            {
-                let mut abilities = target_player.abilities.lock().await;
-                if new_fly_state {
-                    // Activer le vol : activer allow_flying et flying
-                    abilities.allow_flying = true;
-                    abilities.flying = true;
-                } else {
-                    // Désactiver le vol : désactiver flying et allow_flying
-                    abilities.flying = false;
-                    abilities.allow_flying = false;
-                }
-            }
-            target_player.send_abilities_update().await;
+// Toggle flight for a player and return the new fly state
+async fn toggle_fly(target_player: &pumpkin::entity::player::Player) -> bool {
+    // Derive the current fly state from the real abilities, the saved state only
+    // matters in creative and spectator where the gamemode already grants flight
+    let player_uuid = target_player.gameprofile.id;
+    let gamemode_flight = gamemode_grants_flight(target_player.gamemode.load());
+    let is_fly_enabled = if gamemode_flight {
+        get_fly_state(player_uuid).await
+    } else {
+        target_player.abilities.lock().await.allow_flying
+    };
 
-            let player_name = &target_player.gameprofile.name;
-            let status = if new_fly_state { "enabled" } else { "disabled" };
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "Flight mode {}",
-                        status
-                    )))
-                    .await;
-            } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "{} flight mode for {}",
-                        if new_fly_state { "Enabled" } else { "Disabled" },
-                        player_name
-                    )))
-                    .await;
-                    
-                target_player
-                    .send_system_message(&TextComponent::text(format!(
-                        "Flight mode {}",
-                        status
-                    )))
-                    .await;
-            }
+    // Toggle fly state
+    let new_fly_state = !is_fly_enabled;
+    set_fly_state(player_uuid, new_fly_state).await;
 
-            Ok(())
+    // Apply the fly state to the player's abilities, creative and spectator keep their own flight
+    if !gamemode_flight {
+        let mut abilities = target_player.abilities.lock().await;
+        if new_fly_state {
+            // Enable flight and start flying right away
+            abilities.allow_flying = true;
+            abilities.flying = true;
         } else {
-            Err(InvalidRequirement)
+            // Disable flight, stop flying first
+            abilities.flying = false;
+            abilities.allow_flying = false;
         }
     }
+    target_player.send_abilities_update().await;
+
+    new_fly_state
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(FlyExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(FlyExecutor))
-    )
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(FlyExecutor)
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(FlyExecutor))
 }
\ No newline at end of file
@@ -0,0 +1,218 @@
+use async_trait::async_trait;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs, simple::SimpleArgConsumer},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::argument,
+        CommandExecutor, CommandSender,
+    },
+    server::Server,
+};
+use pumpkin_util::GameMode;
+use pumpkin_util::text::TextComponent;
+use crate::player_state::resync_fly;
+
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, sender_name, PlayersOrNickArgumentConsumer,
+};
+
+// /gm <mode> [player]
+const GM_NAMES: [&str; 1] = ["gm"];
+const GM_DESCRIPTION: &str = "Change your gamemode or another player's gamemode.";
+
+// Shortcuts for a single gamemode
+const GMC_NAMES: [&str; 1] = ["gmc"];
+const GMC_DESCRIPTION: &str = "Change your gamemode to creative.";
+const GMS_NAMES: [&str; 1] = ["gms"];
+const GMS_DESCRIPTION: &str = "Change your gamemode to survival.";
+const GMA_NAMES: [&str; 1] = ["gma"];
+const GMA_DESCRIPTION: &str = "Change your gamemode to adventure.";
+const GMSP_NAMES: [&str; 1] = ["gmsp"];
+const GMSP_DESCRIPTION: &str = "Change your gamemode to spectator.";
+
+const ARG_MODE: &str = "mode";
+const ARG_TARGET: &str = "target";
+const NOTIFY_PERMISSION: &str = "pumpkin-essentials:command.gamemode.notify";
+
+pub const GAMEMODES: [GameMode; 4] = [
+    GameMode::Survival,
+    GameMode::Creative,
+    GameMode::Adventure,
+    GameMode::Spectator,
+];
+
+// Lowercase name used in permission nodes, e.g. command.gamemode.creative
+pub fn gamemode_name(gamemode: GameMode) -> &'static str {
+    match gamemode {
+        GameMode::Survival => "survival",
+        GameMode::Creative => "creative",
+        GameMode::Adventure => "adventure",
+        GameMode::Spectator => "spectator",
+    }
+}
+
+// Parse a gamemode from its name, number or abbreviation
+fn parse_gamemode(input: &str) -> Option<GameMode> {
+    match input.to_lowercase().as_str() {
+        "0" | "s" | "surv" | "survival" => Some(GameMode::Survival),
+        "1" | "c" | "creative" => Some(GameMode::Creative),
+        "2" | "a" | "adv" | "adventure" => Some(GameMode::Adventure),
+        "3" | "sp" | "spec" | "spectator" => Some(GameMode::Spectator),
+        _ => None,
+    }
+}
+
+// Change gamemode, with a fixed mode for the shortcuts or the mode argument for /gm
+struct GamemodeExecutor(Option<GameMode>);
+
+#[async_trait]
+impl CommandExecutor for GamemodeExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let gamemode = match self.0 {
+            Some(gamemode) => gamemode,
+            None => {
+                let Some(Arg::Simple(input)) = args.get(ARG_MODE) else {
+                    return Err(InvalidConsumption(Some("Gamemode is required".to_string())));
+                };
+                parse_gamemode(input).ok_or_else(|| InvalidConsumption(Some(format!(
+                    "Unknown gamemode '{}', use survival, creative, adventure or spectator",
+                    input
+                ))))?
+            }
+        };
+        let target_players = resolve_targets(sender, args, ARG_TARGET)?;
+        let mode_name = gamemode_name(gamemode);
+
+        // Check per mode permissions, with separate .others and .exempt nodes for other players
+        let mode_permission = format!("pumpkin-essentials:command.gamemode.{}", mode_name);
+        if !sender.has_permission(&mode_permission).await {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "You don't have permission to use {:?} mode",
+                    gamemode
+                )))
+                .await;
+            return Ok(());
+        }
+        let Some(target_players) = authorize_targets(
+            sender,
+            target_players,
+            &format!("gamemode.{}", mode_name),
+        ).await else {
+            return Ok(());
+        };
+
+        let mut changed_count = 0;
+
+        for target_player in &target_players {
+            if target_player.gamemode.load() == gamemode {
+                continue;
+            }
+
+            let was_flying = target_player.abilities.lock().await.flying;
+            target_player.set_gamemode(gamemode).await;
+            resync_fly(target_player, was_flying).await;
+            changed_count += 1;
+        }
+
+        let message = if target_players.len() == 1 {
+            let target_player = &target_players[0];
+            match (is_self(sender, target_player), changed_count == 1) {
+                (true, true) => format!("Set own gamemode to {:?}", gamemode),
+                (true, false) => format!("You are already in {:?} mode.", gamemode),
+                (false, true) => format!(
+                    "Set {}'s gamemode to {:?}",
+                    target_player.gameprofile.name,
+                    gamemode
+                ),
+                (false, false) => format!(
+                    "{} is already in {:?} mode.",
+                    target_player.gameprofile.name,
+                    gamemode
+                ),
+            }
+        } else {
+            format!(
+                "Set gamemode to {:?} for {} ({} already in {:?} mode)",
+                gamemode,
+                describe_targets(&target_players),
+                target_players.len() - changed_count,
+                gamemode
+            )
+        };
+        sender
+            .send_message(TextComponent::text(message))
+            .await;
+
+        if changed_count > 0 {
+            notify_staff(server, sender, &target_players, gamemode).await;
+        }
+
+        Ok(())
+    }
+}
+
+// Tell staff holding the notify permission about a gamemode change
+async fn notify_staff(
+    server: &Server,
+    sender: &CommandSender,
+    target_players: &[std::sync::Arc<pumpkin::entity::player::Player>],
+    gamemode: GameMode,
+) {
+    let notice = if target_players.len() == 1 && is_self(sender, &target_players[0]) {
+        format!("{} set their gamemode to {:?}", sender_name(sender), gamemode)
+    } else {
+        format!(
+            "{} set the gamemode of {} to {:?}",
+            sender_name(sender),
+            describe_targets(target_players),
+            gamemode
+        )
+    };
+    log::info!("[GAMEMODE] {}", notice);
+
+    for staff in server.get_all_players().await {
+        if !is_self(sender, &staff) && staff.has_permission(NOTIFY_PERMISSION).await {
+            staff
+                .send_system_message(&TextComponent::text(format!("[Staff] {}", notice)))
+                .await;
+        }
+    }
+}
+
+fn shortcut_command_tree(names: [&'static str; 1], description: &'static str, gamemode: GameMode) -> CommandTree {
+    CommandTree::new(names, description)
+        .execute(GamemodeExecutor(Some(gamemode)))
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(GamemodeExecutor(Some(gamemode))))
+}
+
+pub fn init_gm_command_tree() -> CommandTree {
+    CommandTree::new(GM_NAMES, GM_DESCRIPTION).then(
+        argument(ARG_MODE, SimpleArgConsumer)
+            .execute(GamemodeExecutor(None))
+            .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(GamemodeExecutor(None)))
+    )
+}
+
+pub fn init_gmc_command_tree() -> CommandTree {
+    shortcut_command_tree(GMC_NAMES, GMC_DESCRIPTION, GameMode::Creative)
+}
+
+pub fn init_gms_command_tree() -> CommandTree {
+    shortcut_command_tree(GMS_NAMES, GMS_DESCRIPTION, GameMode::Survival)
+}
+
+pub fn init_gma_command_tree() -> CommandTree {
+    shortcut_command_tree(GMA_NAMES, GMA_DESCRIPTION, GameMode::Adventure)
+}
+
+pub fn init_gmsp_command_tree() -> CommandTree {
+    shortcut_command_tree(GMSP_NAMES, GMSP_DESCRIPTION, GameMode::Spectator)
+}
@@ -1,21 +1,33 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::{argument, literal},
         CommandExecutor, CommandSender,
     },
+    plugin::{
+        player::player_damage::PlayerDamageEvent,
+        Cancellable, EventHandler,
+    },
     server::Server,
 };
-use pumpkin::command::CommandSender::Player;
+use pumpkin_api_macros::with_runtime;
 use pumpkin_util::text::TextComponent;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use crate::player_state::{get_state, resync_god, set_god_state};
+
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer,
+};
 
 const NAMES: [&str; 1] = ["god"];
 const DESCRIPTION: &str = "Toggle god mode for yourself or another player.";
 const ARG_TARGET: &str = "target";
+const LIST_PERMISSION: &str = "pumpkin-essentials:command.god.list";
 
 struct GodExecutor;
 
@@ -27,64 +39,143 @@ impl CommandExecutor for GodExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
-            } else {
-                target.clone()
-            };
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "god",
+        ).await else {
+            return Ok(());
+        };
+        let mut enabled_count = 0;
 
-            // Toggle invulnerability
-            let is_invulnerable = target_player.abilities.lock().await.invulnerable;
-            {
-                let mut abilities = target_player.abilities.lock().await;
-                abilities.invulnerable = !is_invulnerable;
+        for target_player in &target_players {
+            // Toggle god mode, the damage handler does the real work and
+            // invulnerability keeps the client in sync
+            let is_god = is_god_enabled(target_player).await;
+            set_god_state(target_player.gameprofile.id, !is_god).await;
+            resync_god(target_player).await;
+            if !is_god {
+                enabled_count += 1;
             }
-            target_player.send_abilities_update().await;
-
-            let player_name = &target_player.gameprofile.name;
-            let status = if !is_invulnerable { "enabled" } else { "disabled" };
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "God mode {}",
-                        status
-                    )))
-                    .await;
+
+            target_player
+                .send_system_message(&TextComponent::text(format!(
+                    "God mode {}",
+                    if !is_god { "enabled" } else { "disabled" }
+                )))
+                .await;
+        }
+
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            let message = if target_players.len() == 1 {
+                format!(
+                    "{} god mode for {}",
+                    if enabled_count == 1 { "Enabled" } else { "Disabled" },
+                    describe_targets(&target_players)
+                )
             } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "{} god mode for {}",
-                        if !is_invulnerable { "Enabled" } else { "Disabled" },
-                        player_name
-                    )))
-                    .await;
-                    
-                target_player
-                    .send_system_message(&TextComponent::text(format!(
-                        "God mode {}",
-                        status
-                    )))
-                    .await;
+                format!(
+                    "Toggled god mode for {} (enabled for {}, disabled for {})",
+                    describe_targets(&target_players),
+                    enabled_count,
+                    target_players.len() - enabled_count
+                )
+            };
+            sender
+                .send_message(TextComponent::text(message))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+// /god list command
+struct GodListExecutor;
+
+#[async_trait]
+impl CommandExecutor for GodListExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if !sender.has_permission(LIST_PERMISSION).await {
+            sender
+                .send_message(TextComponent::text("You don't have permission to list god mode players"))
+                .await;
+            return Ok(());
+        }
+
+        let mut god_players = Vec::new();
+        for player in server.get_all_players().await {
+            if is_god_enabled(&player).await {
+                god_players.push(player.gameprofile.name.clone());
             }
+        }
 
-            Ok(())
+        let message = if god_players.is_empty() {
+            "No online player has god mode enabled".to_string()
         } else {
-            Err(InvalidRequirement)
+            format!(
+                "God mode enabled ({}): {}",
+                god_players.len(),
+                god_players.join(", ")
+            )
+        };
+        sender
+            .send_message(TextComponent::text(message))
+            .await;
+
+        Ok(())
+    }
+}
+
+// Helper function to check if a player currently has god mode
+pub async fn is_god_enabled(player: &pumpkin::entity::player::Player) -> bool {
+    get_state(player.gameprofile.id).await.god
+}
+
+// Event handler for PlayerDamageEvent cancelling every damage source while in god mode
+pub struct GodDamageHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerDamageEvent> for GodDamageHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerDamageEvent) {
+        if is_god_enabled(&event.player).await {
+            event.set_cancelled(true);
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
+// Keep the food level of god mode players from dropping. Pumpkin has no hunger event, so
+// exhaustion is cleared and saturation topped up every second, which keeps food from ever
+// going down in between. The returned task is aborted when the plugin unloads
+pub fn start_hunger_task(server: Arc<Server>) -> JoinHandle<()> {
+    crate::TOKIO_RUNTIME.spawn(async move {
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            for player in server.get_all_players().await {
+                if !is_god_enabled(&player).await {
+                    continue;
+                }
+                player.hunger_manager.exhaustion.store(0.0);
+                player.hunger_manager.saturation.store(5.0);
+                if player.hunger_manager.level.load() < 20 {
+                    player.hunger_manager.level.store(20);
+                    player.send_health().await;
+                }
+            }
+        }
+    })
+}
+
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(GodExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(GodExecutor))
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(GodExecutor)
+        .then(literal("list").execute(GodListExecutor))
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(GodExecutor))
+}
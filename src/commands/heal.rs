@@ -1,21 +1,70 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_data::effect::StatusEffect;
 use pumpkin_util::text::TextComponent;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer,
+};
 
 const NAMES: [&str; 1] = ["heal"];
 const DESCRIPTION: &str = "Heal yourself or another player.";
 const ARG_TARGET: &str = "target";
+const BYPASS_COOLDOWN_PERMISSION: &str = "pumpkin-essentials:command.heal.bypasscooldown";
+
+// Effects removed by /heal
+const NEGATIVE_EFFECTS: [&StatusEffect; 12] = [
+    &StatusEffect::SLOWNESS,
+    &StatusEffect::MINING_FATIGUE,
+    &StatusEffect::INSTANT_DAMAGE,
+    &StatusEffect::NAUSEA,
+    &StatusEffect::BLINDNESS,
+    &StatusEffect::HUNGER,
+    &StatusEffect::WEAKNESS,
+    &StatusEffect::POISON,
+    &StatusEffect::WITHER,
+    &StatusEffect::LEVITATION,
+    &StatusEffect::UNLUCK,
+    &StatusEffect::DARKNESS,
+];
+
+// Last /heal use per player, for the heal cooldown
+lazy_static::lazy_static! {
+    pub static ref HEAL_COOLDOWNS: Arc<Mutex<HashMap<Uuid, Instant>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+}
+
+// Helper function returning the remaining cooldown, or None when the player may heal
+async fn heal_cooldown_remaining(player_uuid: Uuid) -> Option<Duration> {
+    let cooldown = Duration::from_secs(crate::config::get().heal.cooldown_seconds);
+    let mut cooldowns = HEAL_COOLDOWNS.lock().await;
+    let now = Instant::now();
+
+    if let Some(last_heal) = cooldowns.get(&player_uuid) {
+        let elapsed = now.duration_since(*last_heal);
+        if elapsed < cooldown {
+            return Some(cooldown - elapsed);
+        }
+    }
+
+    cooldowns.insert(player_uuid, now);
+    None
+}
 
 struct HealExecutor;
 
@@ -27,51 +76,77 @@ impl CommandExecutor for HealExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "heal",
+        ).await else {
+            return Ok(());
+        };
+
+        // Check heal cooldown, the console has none
+        if let Player(player) = sender {
+            if !player.has_permission(BYPASS_COOLDOWN_PERMISSION).await {
+                if let Some(remaining) = heal_cooldown_remaining(player.gameprofile.id).await {
+                    player
+                        .send_system_message(&TextComponent::text(format!(
+                            "Please wait {}s before healing again",
+                            remaining.as_secs() + 1
+                        )))
+                        .await;
+                    return Ok(());
                 }
-            } else {
-                target.clone()
-            };
-
-            // Set player's health to maximum (20.0)
-            target_player.set_health(20.0).await;
-
-            let player_name = &target_player.gameprofile.name;
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text("You have been healed!"))
-                    .await;
-            } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "Healed {}",
-                        player_name
-                    )))
-                    .await;
-                    
-                target_player
-                    .send_system_message(&TextComponent::text("You have been healed!"))
-                    .await;
             }
+        }
+
+        for target_player in &target_players {
+            heal_player(target_player).await;
+
+            target_player
+                .send_system_message(&TextComponent::text("You have been healed!"))
+                .await;
+        }
+
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Healed {}",
+                    describe_targets(&target_players)
+                )))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+// Restore health to the real maximum and clear ailments according to the config
+pub async fn heal_player(player: &pumpkin::entity::player::Player) {
+    let heal_config = &crate::config::get().heal;
+
+    // Use the max health attribute so modified players are fully healed
+    let max_health = player.living_entity.get_max_health();
+    player.set_health(max_health).await;
+
+    if heal_config.restore_food {
+        player.hunger_manager.level.store(20);
+        player.hunger_manager.saturation.store(5.0);
+        player.send_health().await;
+    }
+
+    if heal_config.extinguish_fire {
+        player.living_entity.entity.extinguish();
+    }
 
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+    if heal_config.clear_negative_effects {
+        for effect in NEGATIVE_EFFECTS {
+            player.living_entity.remove_effect(effect).await;
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(HealExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(HealExecutor))
-    )
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(HealExecutor)
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(HealExecutor))
 }
\ No newline at end of file
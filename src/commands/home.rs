@@ -3,9 +3,8 @@ use pumpkin::{
     command::{
         args::{Arg, ConsumedArgs, simple::SimpleArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -14,6 +13,7 @@ use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
 
 use super::home_common::{PLAYER_HOMES, ARG_HOME_NAME};
+use super::targets::player_required;
 
 const NAMES: [&str; 1] = ["home"];
 const DESCRIPTION: &str = "Teleport to your home.";
@@ -83,16 +83,13 @@ impl CommandExecutor for HomeExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(HomeExecutor)
-            .then(argument(ARG_HOME_NAME, SimpleArgConsumer).execute(HomeExecutor))
-    )
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(HomeExecutor)
+        .then(argument(ARG_HOME_NAME, SimpleArgConsumer).execute(HomeExecutor))
 }
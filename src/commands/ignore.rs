@@ -1,21 +1,93 @@
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::{Arg, ConsumedArgs, simple::SimpleArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::{argument, literal},
         CommandExecutor, CommandSender,
     },
+    plugin::{player::player_chat::PlayerChatEvent, Cancellable, EventHandler},
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_api_macros::with_runtime;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
 use pumpkin_util::text::TextComponent;
 
+use super::nick::{display_name, find_by_nickname};
+use super::targets::player_required;
+use crate::player_state::find_player_uuid;
+use crate::storage;
+use crate::text_format::parse_codes;
+
 const NAMES: [&str; 1] = ["ignore"];
 const DESCRIPTION: &str = "Ignore a player.";
 const ARG_TARGET: &str = "target";
+const EXEMPT_PERMISSION: &str = "pumpkin-essentials:command.ignore.exempt";
+const IGNORES_FILE: &str = "ignores.yml";
+
+// Ignored players per player, with the last known name for /ignore list
+static IGNORE_LISTS: Lazy<Arc<Mutex<HashMap<Uuid, HashMap<Uuid, String>>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Load saved ignore lists from disk
+pub async fn load() {
+    let ignores: HashMap<Uuid, HashMap<Uuid, String>> = storage::load_yaml(IGNORES_FILE);
+    *IGNORE_LISTS.lock().await = ignores;
+}
+
+// Helper function to check if a player ignores another one
+pub async fn is_ignoring(player_uuid: Uuid, other_uuid: Uuid) -> bool {
+    let ignores = IGNORE_LISTS.lock().await;
+    ignores
+        .get(&player_uuid)
+        .is_some_and(|ignored| ignored.contains_key(&other_uuid))
+}
+
+// Online players who should see chat from a player, everyone except those ignoring them
+pub async fn chat_recipients(server: &Server, sender_uuid: Uuid) -> Vec<Arc<pumpkin::entity::player::Player>> {
+    let mut recipients = Vec::new();
+    for player in server.get_all_players().await {
+        if !is_ignoring(player.gameprofile.id, sender_uuid).await {
+            recipients.push(player);
+        }
+    }
+    recipients
+}
+
+// Helper function to find a player on someone's ignore list by the name saved with it
+async fn find_ignored_by_name(player_uuid: Uuid, name: &str) -> Option<(Uuid, String)> {
+    let ignores = IGNORE_LISTS.lock().await;
+    ignores
+        .get(&player_uuid)?
+        .iter()
+        .find(|(_, ignored_name)| ignored_name.eq_ignore_ascii_case(name))
+        .map(|(uuid, ignored_name)| (*uuid, ignored_name.clone()))
+}
+
+// Add or remove a player from an ignore list, returns true when they are now ignored
+async fn toggle_ignore(player_uuid: Uuid, other_uuid: Uuid, other_name: &str) -> bool {
+    let mut ignores = IGNORE_LISTS.lock().await;
+    let ignored = ignores.entry(player_uuid).or_default();
+    let now_ignored = if ignored.remove(&other_uuid).is_some() {
+        false
+    } else {
+        ignored.insert(other_uuid, other_name.to_string());
+        true
+    };
+    if ignored.is_empty() {
+        ignores.remove(&player_uuid);
+    }
+
+    storage::save_yaml(IGNORES_FILE, &*ignores);
+    now_ignored
+}
 
 struct IgnoreExecutor;
 
@@ -24,44 +96,145 @@ impl CommandExecutor for IgnoreExecutor {
     async fn execute<'a>(
         &self,
         sender: &mut CommandSender,
-        _server: &Server,
+        server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
         if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
-            } else {
+            let Some(Arg::Simple(target_name)) = args.get(ARG_TARGET) else {
                 return Err(InvalidConsumption(Some("Target player is required".to_string())));
             };
 
-            // TODO: Implement ignore functionality
-            // This would require a system to track ignored players and filter chat messages
-            // For now, we'll show a placeholder message
-            
-            let player_name = &target_player.gameprofile.name;
-            
+            // Online players by name or nickname, then names from the ignore list or of
+            // players who joined before, so offline players can still be un-ignored
+            let online_player = match server.get_player_by_name(target_name).await {
+                Some(player) => Some(player),
+                None => find_by_nickname(server, target_name).await,
+            };
+            let found = match &online_player {
+                Some(player) => Some((player.gameprofile.id, player.gameprofile.name.clone())),
+                None => match find_ignored_by_name(target.gameprofile.id, target_name).await {
+                    Some(found) => Some(found),
+                    None => find_player_uuid(server, target_name).await,
+                },
+            };
+            let Some((other_uuid, player_name)) = found else {
+                target
+                    .send_system_message(&TextComponent::text(format!("Player '{}' has never joined", target_name)))
+                    .await;
+                return Ok(());
+            };
+
+            if target.gameprofile.id == other_uuid {
+                target
+                    .send_system_message(&TextComponent::text("You cannot ignore yourself"))
+                    .await;
+                return Ok(());
+            }
+
+            // Adding needs the player online to check their exempt permission. Staff can't
+            // be ignored, but can still be removed from an old ignore list
+            if !is_ignoring(target.gameprofile.id, other_uuid).await {
+                let Some(other_player) = &online_player else {
+                    target
+                        .send_system_message(&TextComponent::text(format!("{} must be online to be ignored", player_name)))
+                        .await;
+                    return Ok(());
+                };
+                if other_player.has_permission(EXEMPT_PERMISSION).await {
+                    target
+                        .send_system_message(&TextComponent::text(format!("You cannot ignore {}", player_name)))
+                        .await;
+                    return Ok(());
+                }
+            }
+
+            let now_ignored = toggle_ignore(target.gameprofile.id, other_uuid, &player_name).await;
+
             target
-                .send_system_message(&TextComponent::text(format!(
-                    "Player ignore functionality is not yet implemented. (Would ignore: {})",
-                    player_name
-                )))
+                .send_system_message(&TextComponent::text(if now_ignored {
+                    format!("You are now ignoring {}", player_name)
+                } else {
+                    format!("You are no longer ignoring {}", player_name)
+                }))
                 .await;
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
+// /ignore list command
+struct IgnoreListExecutor;
+
+#[async_trait]
+impl CommandExecutor for IgnoreListExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let Player(target) = sender {
+            let mut names: Vec<String> = {
+                let ignores = IGNORE_LISTS.lock().await;
+                ignores
+                    .get(&target.gameprofile.id)
+                    .map(|ignored| ignored.values().cloned().collect())
+                    .unwrap_or_default()
+            };
+            names.sort();
+
+            let message = if names.is_empty() {
+                "You are not ignoring anyone".to_string()
+            } else {
+                format!("Ignored players ({}): {}", names.len(), names.join(", "))
+            };
+            target
+                .send_system_message(&TextComponent::text(message))
+                .await;
+
+            Ok(())
+        } else {
+            Err(player_required())
+        }
+    }
+}
+
+// Event handler for PlayerChatEvent hiding messages from players who ignore the sender when
+// the chat format is off. With the format on, ChatFormatHandler skips those players itself
+pub struct IgnoreChatHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerChatEvent> for IgnoreChatHandler {
+    async fn handle_blocking(&self, server: &Arc<Server>, event: &mut PlayerChatEvent) {
+        if crate::config::get().chat.enabled || event.cancelled() {
+            return;
+        }
+
+        // Vanilla chat goes to everyone, so it is only replaced when someone ignores the sender
+        let online_count = server.get_all_players().await.len();
+        let recipients = chat_recipients(server, event.player.gameprofile.id).await;
+        if recipients.len() == online_count {
+            return;
+        }
+
+        // Same look as vanilla chat, with the nickname like the chat format would show
+        let chat_line = TextComponent::text("<")
+            .add_child(parse_codes(&display_name(&event.player).await))
+            .add_child(TextComponent::text(format!("> {}", event.message)));
+        log::info!("[CHAT] {}: {}", event.player.gameprofile.name, event.message);
+        for recipient in &recipients {
+            recipient.send_system_message(&chat_line).await;
+        }
+        event.set_cancelled(true);
+    }
+}
+
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(IgnoreExecutor))
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(literal("list").execute(IgnoreListExecutor))
+        .then(argument(ARG_TARGET, SimpleArgConsumer).execute(IgnoreExecutor))
+}
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::argument,
+        CommandExecutor, CommandSender,
+    },
+    entity::player::Player,
+    server::Server,
+};
+use pumpkin::command::CommandSender::Player as PlayerSender;
+use pumpkin_util::text::TextComponent;
+use pumpkin_world::inventory::{Clearable, Inventory};
+use pumpkin_world::item::ItemStack;
+use std::any::Any;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::container_view::ContainerViewFactory;
+use super::targets::{is_self, player_required, PlayersOrNickArgumentConsumer};
+
+const NAMES: [&str; 1] = ["invsee"];
+const DESCRIPTION: &str = "View or edit another player's inventory.";
+const ARG_TARGET: &str = "target";
+const MODIFY_PERMISSION: &str = "pumpkin-essentials:command.invsee.modify";
+const EXEMPT_PERMISSION: &str = "pumpkin-essentials:command.invsee.exempt";
+
+// 36 main slots, 4 armor slots and the offhand, padded to 5 chest rows
+const VIEW_SIZE: usize = 45;
+
+struct InvseeExecutor;
+
+#[async_trait]
+impl CommandExecutor for InvseeExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let PlayerSender(viewer) = sender {
+            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
+                if players.len() == 1 {
+                    players[0].clone()
+                } else {
+                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+                }
+            } else {
+                return Err(InvalidConsumption(Some("Target player is required".to_string())));
+            };
+
+            if is_self(sender, &target_player) {
+                viewer
+                    .send_system_message(&TextComponent::text("You can't use /invsee on yourself"))
+                    .await;
+                return Ok(());
+            }
+
+            if target_player.has_permission(EXEMPT_PERMISSION).await {
+                viewer
+                    .send_system_message(&TextComponent::text("That player's inventory can't be inspected"))
+                    .await;
+                return Ok(());
+            }
+
+            // Read-only view without the modify permission. The screen handler refuses every
+            // click, shift-click and drag on the target's slots, the same guard as /enderchest
+            let editable = viewer.has_permission(MODIFY_PERMISSION).await;
+            let factory = ContainerViewFactory::new(
+                Arc::new(InvseeInventory { target: target_player.clone() }),
+                format!("{}'s Inventory", target_player.gameprofile.name),
+                editable,
+            );
+            viewer.open_handled_screen(&factory).await;
+
+            if !editable {
+                viewer
+                    .send_system_message(&TextComponent::text(format!(
+                        "Viewing {}'s inventory in read-only mode",
+                        target_player.gameprofile.name
+                    )))
+                    .await;
+            }
+
+            Ok(())
+        } else {
+            Err(player_required())
+        }
+    }
+}
+
+// The target's inventory laid out as a chest: main inventory and hotbar first, then
+// armor and offhand. Slots are the target's own stacks, so both sides see changes live
+struct InvseeInventory {
+    target: Arc<Player>,
+}
+
+impl InvseeInventory {
+    fn is_player_slot(&self, slot: usize) -> bool {
+        slot < self.target.inventory().size()
+    }
+}
+
+#[async_trait]
+impl Inventory for InvseeInventory {
+    fn size(&self) -> usize {
+        VIEW_SIZE
+    }
+
+    async fn is_empty(&self) -> bool {
+        self.target.inventory().is_empty().await
+    }
+
+    async fn get_stack(&self, slot: usize) -> Arc<Mutex<ItemStack>> {
+        if self.is_player_slot(slot) {
+            self.target.inventory().get_stack(slot).await
+        } else {
+            // Padding slots at the end of the last row
+            Arc::new(Mutex::new(ItemStack::EMPTY.clone()))
+        }
+    }
+
+    async fn remove_stack(&self, slot: usize) -> ItemStack {
+        if self.is_player_slot(slot) {
+            self.target.inventory().remove_stack(slot).await
+        } else {
+            ItemStack::EMPTY.clone()
+        }
+    }
+
+    async fn remove_stack_specific(&self, slot: usize, amount: u8) -> ItemStack {
+        if self.is_player_slot(slot) {
+            self.target.inventory().remove_stack_specific(slot, amount).await
+        } else {
+            ItemStack::EMPTY.clone()
+        }
+    }
+
+    async fn set_stack(&self, slot: usize, stack: ItemStack) {
+        if self.is_player_slot(slot) {
+            self.target.inventory().set_stack(slot, stack).await;
+        }
+    }
+
+    fn is_valid_slot_for(&self, slot: usize, stack: &ItemStack) -> bool {
+        self.is_player_slot(slot) && self.target.inventory().is_valid_slot_for(slot, stack)
+    }
+
+    // Push the viewer's edits to the target's client right away
+    fn mark_dirty(&self) {
+        let target = self.target.clone();
+        crate::TOKIO_RUNTIME.spawn(async move {
+            target.sync_inventory().await;
+        });
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl Clearable for InvseeInventory {
+    async fn clear(&self) {}
+}
+
+pub fn init_command_tree() -> CommandTree {
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(InvseeExecutor))
+}
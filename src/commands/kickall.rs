@@ -1,20 +1,23 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::ConsumedArgs,
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::require,
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
-use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
 
+use super::targets::{is_self, sender_name};
+
 const NAMES: [&str; 1] = ["kickall"];
 const DESCRIPTION: &str = "Kick all players from the server.";
+const ARG_REASON: &str = "reason";
+const EXEMPT_PERMISSION: &str = "pumpkin-essentials:command.kickall.exempt";
+const DEFAULT_REASON: &str = "Kicked by an operator";
 
 struct KickallExecutor;
 
@@ -24,32 +27,50 @@ impl CommandExecutor for KickallExecutor {
         &self,
         sender: &mut CommandSender,
         server: &Server,
-        _args: &ConsumedArgs<'a>,
+        args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            // TODO: Implement kick all functionality
-            // This would require access to the server's player list and kick functionality
-            // For now, we'll show a placeholder message
-            
-            let player_count = server.get_player_count().await;
-            
-            target
-                .send_system_message(&TextComponent::text(format!(
-                    "Kick all functionality is not yet implemented. (Would kick {} players)",
-                    player_count
-                )))
-                .await;
-
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+        let reason = match args.get(ARG_REASON) {
+            Some(Arg::Msg(reason)) => reason.clone(),
+            _ => DEFAULT_REASON.to_string(),
+        };
+
+        let mut kicked_count = 0;
+        let mut exempt_count = 0;
+
+        for player in server.get_all_players().await {
+            // Never kick whoever ran the command
+            if is_self(sender, &player) {
+                continue;
+            }
+            if player.has_permission(EXEMPT_PERMISSION).await {
+                exempt_count += 1;
+                continue;
+            }
+
+            player.kick(TextComponent::text(reason.clone())).await;
+            kicked_count += 1;
         }
+
+        log::info!(
+            "[KICKALL] {} kicked {} players ({})",
+            sender_name(sender),
+            kicked_count,
+            reason
+        );
+
+        sender
+            .send_message(TextComponent::text(format!(
+                "Kicked {} players ({} exempt)",
+                kicked_count, exempt_count
+            )))
+            .await;
+
+        Ok(())
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(KickallExecutor)
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(KickallExecutor)
+        .then(argument(ARG_REASON, MsgArgConsumer).execute(KickallExecutor))
+}
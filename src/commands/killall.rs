@@ -1,20 +1,201 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::ConsumedArgs,
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::require,
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
+    entity::{Entity, EntityBase, RemovalReason},
     server::Server,
+    world::World,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_data::entity::{EntityType, MobCategory};
+use pumpkin_util::math::vector3::Vector3;
 use pumpkin_util::text::TextComponent;
+use std::sync::Arc;
+
+use super::targets::player_required;
 
 const NAMES: [&str; 1] = ["killall"];
 const DESCRIPTION: &str = "Kill all entities in the world.";
+const ARG_FILTERS: &str = "filters";
+
+// Which entities /killall removes, monsters when no type is given
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KillallTarget {
+    Mobs,
+    Monsters,
+    Animals,
+    Items,
+    ExperienceOrbs,
+    Type(&'static EntityType),
+}
+
+// Parsed `/killall [type] [radius] [world] [flags]`, e.g. `/killall animals 50 --named --dry-run`.
+// The world can also be given as `world:<name>`, which the console needs when no type is given
+#[derive(Debug)]
+struct KillallOptions {
+    target: KillallTarget,
+    radius: Option<f64>,
+    world: Option<String>,
+    include_named: bool,
+    include_tamed: bool,
+    include_leashed: bool,
+    include_vehicles: bool,
+    dry_run: bool,
+}
+
+fn parse_target(input: &str) -> Option<KillallTarget> {
+    match input.to_lowercase().as_str() {
+        "mobs" | "all" => Some(KillallTarget::Mobs),
+        "monsters" | "hostile" => Some(KillallTarget::Monsters),
+        "animals" | "passive" => Some(KillallTarget::Animals),
+        "items" | "drops" => Some(KillallTarget::Items),
+        "xp" | "orbs" => Some(KillallTarget::ExperienceOrbs),
+        name => EntityType::from_name(name.strip_prefix("minecraft:").unwrap_or(name))
+            .filter(|entity_type| **entity_type != EntityType::PLAYER)
+            .map(KillallTarget::Type),
+    }
+}
+
+fn parse_options(input: &str) -> Result<KillallOptions, String> {
+    let mut options = KillallOptions {
+        target: KillallTarget::Monsters,
+        radius: None,
+        world: None,
+        include_named: false,
+        include_tamed: false,
+        include_leashed: false,
+        include_vehicles: false,
+        dry_run: false,
+    };
+
+    let mut positional = 0;
+    for token in input.split_whitespace() {
+        match token {
+            "--dry-run" => options.dry_run = true,
+            "--named" => options.include_named = true,
+            "--tamed" => options.include_tamed = true,
+            "--leashed" => options.include_leashed = true,
+            "--vehicles" => options.include_vehicles = true,
+            flag if flag.starts_with("--") => {
+                return Err(format!(
+                    "Unknown flag '{}', expected --named, --tamed, --leashed, --vehicles or --dry-run",
+                    flag
+                ));
+            }
+            _ if token.starts_with("world:") => {
+                let name = &token["world:".len()..];
+                if name.is_empty() || options.world.is_some() {
+                    return Err(format!("Invalid world '{}'", token));
+                }
+                options.world = Some(name.to_lowercase());
+            }
+            _ => {
+                // Positional arguments in order: type, radius, world. The type and the radius
+                // may be left out, so anything that isn't a number after the type is the world
+                let is_number = token.parse::<f64>().is_ok();
+                if positional == 0 && !is_number {
+                    options.target = parse_target(token)
+                        .ok_or_else(|| format!("Unknown entity type '{}'", token))?;
+                    positional = 1;
+                } else if positional <= 1 && is_number {
+                    match token.parse::<f64>() {
+                        Ok(radius) if radius.is_finite() && radius > 0.0 => options.radius = Some(radius),
+                        _ => return Err(format!("Invalid radius '{}'", token)),
+                    }
+                    positional = 2;
+                } else if positional <= 2 && !is_number && options.world.is_none() {
+                    options.world = Some(token.to_lowercase());
+                    positional = 3;
+                } else {
+                    return Err(format!("Unexpected argument '{}'", token));
+                }
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+// Helper function to check if an entity type is a boat, raft or minecart
+fn is_vehicle(entity_type: &EntityType) -> bool {
+    let name = entity_type.resource_name;
+    name.ends_with("_boat") || name.ends_with("_raft") || name.contains("minecart")
+}
+
+fn matches_target(target: KillallTarget, entity: &dyn EntityBase) -> bool {
+    let entity_type = entity.get_entity().entity_type;
+    if *entity_type == EntityType::PLAYER {
+        return false;
+    }
+
+    match target {
+        KillallTarget::Mobs => {
+            entity.get_living_entity().is_some() && entity_type.category != MobCategory::MISC
+        }
+        KillallTarget::Monsters => entity_type.category == MobCategory::MONSTER,
+        KillallTarget::Animals => {
+            entity.get_living_entity().is_some()
+                && !matches!(entity_type.category, MobCategory::MONSTER | MobCategory::MISC)
+        }
+        KillallTarget::Items => *entity_type == EntityType::ITEM,
+        KillallTarget::ExperienceOrbs => *entity_type == EntityType::EXPERIENCE_ORB,
+        KillallTarget::Type(wanted) => entity_type == wanted,
+    }
+}
+
+// Named, tamed, leashed entities and vehicles are kept unless a flag includes them
+fn is_protected(options: &KillallOptions, entity: &Entity) -> bool {
+    (!options.include_named && entity.custom_name.is_some())
+        || (!options.include_tamed && entity.is_tamed())
+        || (!options.include_leashed && entity.is_leashed())
+        || (!options.include_vehicles && is_vehicle(entity.entity_type))
+}
+
+// Remove (or only count, for --dry-run) the matching entities of one world
+async fn killall_in_world(
+    world: &Arc<World>,
+    options: &KillallOptions,
+    center: Option<Vector3<f64>>,
+) -> (usize, usize) {
+    let entities: Vec<Arc<dyn EntityBase>> = world.entities.read().await.values().cloned().collect();
+    let mut removed = 0;
+    let mut protected = 0;
+
+    for entity in entities {
+        if !matches_target(options.target, entity.as_ref()) {
+            continue;
+        }
+
+        let base = entity.get_entity();
+        if let (Some(center), Some(radius)) = (center, options.radius) {
+            let pos = base.pos.load();
+            let dx = pos.x - center.x;
+            let dy = pos.y - center.y;
+            let dz = pos.z - center.z;
+            if dx * dx + dy * dy + dz * dz > radius * radius {
+                continue;
+            }
+        }
+
+        if is_protected(options, base) {
+            protected += 1;
+            continue;
+        }
+
+        if !options.dry_run {
+            world.remove_entity(base, RemovalReason::Killed).await;
+        }
+        removed += 1;
+    }
+
+    (removed, protected)
+}
 
 struct KillallExecutor;
 
@@ -23,28 +204,80 @@ impl CommandExecutor for KillallExecutor {
     async fn execute<'a>(
         &self,
         sender: &mut CommandSender,
-        _server: &Server,
-        _args: &ConsumedArgs<'a>,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            // TODO: Implement entity killing functionality
-            // This would require access to the world's entity system
-            // For now, we'll show a placeholder message
-            
-            target
-                .send_system_message(&TextComponent::text("Entity killing functionality is not yet implemented."))
-                .await;
-
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+        let options = match args.get(ARG_FILTERS) {
+            Some(Arg::Msg(input)) => parse_options(input).map_err(|message| InvalidConsumption(Some(message)))?,
+            _ => parse_options("").map_err(|message| InvalidConsumption(Some(message)))?,
+        };
+
+        // The radius is measured from the sender, so the console can't use one
+        let (sender_world, center) = match sender {
+            Player(player) => (Some(player.world().await), Some(player.living_entity.entity.pos.load())),
+            _ if options.radius.is_some() => return Err(player_required()),
+            _ => (None, None),
+        };
+
+        // Selected world, otherwise the sender's world, otherwise every world from the console
+        let worlds: Vec<Arc<World>> = match &options.world {
+            Some(name) => match crate::find_world(server, name).await {
+                Some(world) => vec![world],
+                None => {
+                    sender
+                        .send_message(TextComponent::text(format!(
+                            "Unknown world '{}', expected one of {}",
+                            name,
+                            crate::world_names(server).await.join(", ")
+                        )))
+                        .await;
+                    return Ok(());
+                }
+            },
+            None => match sender_world {
+                Some(world) => vec![world],
+                None => server.worlds.read().await.clone(),
+            },
+        };
+
+        // The radius only makes sense in the world the sender is standing in
+        if options.radius.is_some() && options.world.is_some() {
+            if let Player(player) = sender {
+                if !Arc::ptr_eq(&worlds[0], &player.world().await) {
+                    sender
+                        .send_message(TextComponent::text("A radius can only be used in your current world"))
+                        .await;
+                    return Ok(());
+                }
+            }
         }
+
+        let mut removed = 0;
+        let mut protected = 0;
+        for world in &worlds {
+            let (world_removed, world_protected) = killall_in_world(world, &options, center).await;
+            removed += world_removed;
+            protected += world_protected;
+        }
+
+        let message = if options.dry_run {
+            format!(
+                "Dry run: {} entities would be removed ({} protected)",
+                removed, protected
+            )
+        } else {
+            format!("Removed {} entities ({} protected)", removed, protected)
+        };
+        sender
+            .send_message(TextComponent::text(message))
+            .await;
+
+        Ok(())
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(KillallExecutor)
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(KillallExecutor)
+        .then(argument(ARG_FILTERS, MsgArgConsumer).execute(KillallExecutor))
+}
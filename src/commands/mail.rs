@@ -0,0 +1,375 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer, simple::SimpleArgConsumer},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::{argument, literal},
+        CommandExecutor, CommandSender,
+    },
+    plugin::{player::player_join::PlayerJoinEvent, EventHandler},
+    server::Server,
+};
+use pumpkin::command::CommandSender::Player;
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::ignore::is_ignoring;
+use super::targets::{player_required, sender_name};
+use crate::player_state::{find_player_uuid, known_player_uuids};
+use crate::{config, storage};
+
+const NAMES: [&str; 1] = ["mail"];
+const DESCRIPTION: &str = "Send and read mail, even to offline players.";
+const ARG_TARGET: &str = "target";
+const ARG_MESSAGE: &str = "message";
+const SENDALL_PERMISSION: &str = "pumpkin-essentials:command.mail.sendall";
+const BYPASS_COOLDOWN_PERMISSION: &str = "pumpkin-essentials:command.mail.bypasscooldown";
+const MAIL_FILE: &str = "mail.yml";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mail {
+    pub from: String,
+    pub message: String,
+    pub read: bool,
+}
+
+// Mailbox of every player
+static MAILBOXES: Lazy<Arc<Mutex<HashMap<Uuid, Vec<Mail>>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Last mail sent by each sender (None is the console), for the send cooldown
+static LAST_SENT: Lazy<Arc<Mutex<HashMap<Option<Uuid>, Instant>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Load saved mailboxes from disk
+pub async fn load() {
+    let mailboxes: HashMap<Uuid, Vec<Mail>> = storage::load_yaml(MAIL_FILE);
+    *MAILBOXES.lock().await = mailboxes;
+}
+
+// Check the per-sender cooldown, None when the sender may send
+async fn mail_cooldown_remaining(sender: &CommandSender) -> Option<Duration> {
+    let sender_uuid = match sender {
+        Player(player) => Some(player.gameprofile.id),
+        _ => return None,
+    };
+    if sender.has_permission(BYPASS_COOLDOWN_PERMISSION).await {
+        return None;
+    }
+
+    let cooldown = Duration::from_secs(config::get().mail.send_cooldown_seconds);
+    let last_sent = LAST_SENT.lock().await;
+    let elapsed = last_sent.get(&sender_uuid)?.elapsed();
+    cooldown.checked_sub(elapsed).filter(|remaining| !remaining.is_zero())
+}
+
+// Start the sender's cooldown once their mail was delivered
+async fn start_mail_cooldown(sender: &CommandSender) {
+    if let Player(player) = sender {
+        LAST_SENT.lock().await.insert(Some(player.gameprofile.id), Instant::now());
+    }
+}
+
+// Add a mail to the mailbox of every recipient with room left, saving once.
+// Returns the recipients who got it
+async fn deliver_mail(recipients: &[Uuid], mail: &Mail) -> Vec<Uuid> {
+    let max_mailbox_size = config::get().mail.max_mailbox_size;
+    let mut mailboxes = MAILBOXES.lock().await;
+    let mut delivered = Vec::new();
+    for recipient_uuid in recipients {
+        let mailbox = mailboxes.entry(*recipient_uuid).or_default();
+        if mailbox.len() < max_mailbox_size {
+            mailbox.push(mail.clone());
+            delivered.push(*recipient_uuid);
+        }
+    }
+    if !delivered.is_empty() {
+        storage::save_yaml(MAIL_FILE, &*mailboxes);
+    }
+    delivered
+}
+
+// Tell an online player about new mail
+async fn notify_recipient(server: &Server, recipient_uuid: Uuid, from: &str) {
+    if let Some(recipient) = server.get_player_by_uuid(recipient_uuid).await {
+        recipient
+            .send_system_message(&TextComponent::text(format!(
+                "You have new mail from {}, use /mail read",
+                from
+            )))
+            .await;
+    }
+}
+
+// /mail send <player> <message>
+struct MailSendExecutor;
+
+#[async_trait]
+impl CommandExecutor for MailSendExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(Arg::Simple(target_name)) = args.get(ARG_TARGET) else {
+            return Err(InvalidConsumption(Some("Target player is required".to_string())));
+        };
+        let Some(Arg::Msg(message)) = args.get(ARG_MESSAGE) else {
+            return Err(InvalidConsumption(Some("Message is required".to_string())));
+        };
+
+        let Some((recipient_uuid, recipient_name)) = find_player_uuid(server, target_name).await else {
+            sender
+                .send_message(TextComponent::text(format!("Player '{}' has never joined", target_name)))
+                .await;
+            return Ok(());
+        };
+
+        // Mail from ignored players is never delivered
+        if let Player(player) = sender {
+            if is_ignoring(recipient_uuid, player.gameprofile.id).await {
+                sender
+                    .send_message(TextComponent::text(format!(
+                        "{} is not accepting mail from you",
+                        recipient_name
+                    )))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        if let Some(remaining) = mail_cooldown_remaining(sender).await {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Please wait {}s before sending more mail",
+                    remaining.as_secs() + 1
+                )))
+                .await;
+            return Ok(());
+        }
+
+        let from = sender_name(sender);
+        let mail = Mail { from: from.clone(), message: message.clone(), read: false };
+        if deliver_mail(&[recipient_uuid], &mail).await.is_empty() {
+            sender
+                .send_message(TextComponent::text(format!("{}'s mailbox is full", recipient_name)))
+                .await;
+            return Ok(());
+        }
+
+        start_mail_cooldown(sender).await;
+        notify_recipient(server, recipient_uuid, &from).await;
+        sender
+            .send_message(TextComponent::text(format!("Mail sent to {}", recipient_name)))
+            .await;
+
+        Ok(())
+    }
+}
+
+// /mail sendall <message>
+struct MailSendallExecutor;
+
+#[async_trait]
+impl CommandExecutor for MailSendallExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if !sender.has_permission(SENDALL_PERMISSION).await {
+            sender
+                .send_message(TextComponent::text("You don't have permission to send mail to everyone"))
+                .await;
+            return Ok(());
+        }
+
+        let Some(Arg::Msg(message)) = args.get(ARG_MESSAGE) else {
+            return Err(InvalidConsumption(Some("Message is required".to_string())));
+        };
+
+        if let Some(remaining) = mail_cooldown_remaining(sender).await {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Please wait {}s before sending more mail",
+                    remaining.as_secs() + 1
+                )))
+                .await;
+            return Ok(());
+        }
+
+        // Everyone who ever joined except the sender and players ignoring them
+        let sender_uuid = match sender {
+            Player(player) => Some(player.gameprofile.id),
+            _ => None,
+        };
+        let mut recipients = Vec::new();
+        for recipient_uuid in known_player_uuids().await {
+            if Some(recipient_uuid) == sender_uuid {
+                continue;
+            }
+            if let Some(sender_uuid) = sender_uuid {
+                if is_ignoring(recipient_uuid, sender_uuid).await {
+                    continue;
+                }
+            }
+            recipients.push(recipient_uuid);
+        }
+
+        let from = sender_name(sender);
+        let mail = Mail { from: from.clone(), message: message.clone(), read: false };
+        let delivered = deliver_mail(&recipients, &mail).await;
+        if !delivered.is_empty() {
+            start_mail_cooldown(sender).await;
+        }
+        for recipient_uuid in &delivered {
+            notify_recipient(server, *recipient_uuid, &from).await;
+        }
+
+        let full_count = recipients.len() - delivered.len();
+        let message = if full_count > 0 {
+            format!("Mail sent to {} players ({} mailboxes full)", delivered.len(), full_count)
+        } else {
+            format!("Mail sent to {} players", delivered.len())
+        };
+        sender
+            .send_message(TextComponent::text(message))
+            .await;
+
+        Ok(())
+    }
+}
+
+// /mail read
+struct MailReadExecutor;
+
+#[async_trait]
+impl CommandExecutor for MailReadExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let Player(target) = sender {
+            let mails = {
+                let mut mailboxes = MAILBOXES.lock().await;
+                let mails = mailboxes.get(&target.gameprofile.id).cloned().unwrap_or_default();
+                if let Some(mailbox) = mailboxes.get_mut(&target.gameprofile.id) {
+                    if mailbox.iter().any(|mail| !mail.read) {
+                        mailbox.iter_mut().for_each(|mail| mail.read = true);
+                        storage::save_yaml(MAIL_FILE, &*mailboxes);
+                    }
+                }
+                mails
+            };
+
+            if mails.is_empty() {
+                target
+                    .send_system_message(&TextComponent::text("You have no mail"))
+                    .await;
+                return Ok(());
+            }
+
+            target
+                .send_system_message(&TextComponent::text(format!(
+                    "Mail ({}), use /mail clear to delete it:",
+                    mails.len()
+                )))
+                .await;
+            for mail in &mails {
+                target
+                    .send_system_message(&TextComponent::text(format!(
+                        "{}[{}] {}",
+                        if mail.read { "" } else { "(new) " },
+                        mail.from,
+                        mail.message
+                    )))
+                    .await;
+            }
+
+            Ok(())
+        } else {
+            Err(player_required())
+        }
+    }
+}
+
+// /mail clear
+struct MailClearExecutor;
+
+#[async_trait]
+impl CommandExecutor for MailClearExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let Player(target) = sender {
+            let mut mailboxes = MAILBOXES.lock().await;
+            let cleared = mailboxes.remove(&target.gameprofile.id).map_or(0, |mailbox| mailbox.len());
+            storage::save_yaml(MAIL_FILE, &*mailboxes);
+
+            target
+                .send_system_message(&TextComponent::text(format!("Deleted {} mails", cleared)))
+                .await;
+
+            Ok(())
+        } else {
+            Err(player_required())
+        }
+    }
+}
+
+// Event handler for PlayerJoinEvent announcing unread mail
+pub struct MailJoinHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerJoinEvent> for MailJoinHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerJoinEvent) {
+        let player_uuid = event.player.gameprofile.id;
+        let unread = MAILBOXES
+            .lock()
+            .await
+            .get(&player_uuid)
+            .map_or(0, |mailbox| mailbox.iter().filter(|mail| !mail.read).count());
+        if unread > 0 {
+            event
+                .player
+                .send_system_message(&TextComponent::text(format!(
+                    "You have {} unread mail, use /mail read",
+                    unread
+                )))
+                .await;
+        }
+    }
+}
+
+pub fn init_command_tree() -> CommandTree {
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(
+            literal("send").then(
+                argument(ARG_TARGET, SimpleArgConsumer)
+                    .then(argument(ARG_MESSAGE, MsgArgConsumer).execute(MailSendExecutor))
+            )
+        )
+        .then(
+            literal("sendall")
+                .then(argument(ARG_MESSAGE, MsgArgConsumer).execute(MailSendallExecutor))
+        )
+        .then(literal("read").execute(MailReadExecutor))
+        .then(literal("clear").execute(MailClearExecutor))
+}
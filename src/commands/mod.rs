@@ -1,12 +1,11 @@
 pub mod home_common;
+pub mod targets;
+pub mod container_view;
 pub mod home;
 pub mod sethome;
 pub mod delhome;
 pub mod back;
-pub mod gmc;
-pub mod gms;
-pub mod gma;
-pub mod gmsp;
+pub mod gamemode;
 pub mod top;
 pub mod tpa;
 pub mod tpahere;
@@ -21,7 +20,12 @@ pub mod repair;
 pub mod suicide;
 pub mod killall;
 pub mod enderchest;
+pub mod invsee;
 pub mod ignore;
+pub mod msg;
+pub mod mail;
+pub mod mute;
+pub mod nick;
 pub mod kickall;
 pub mod sudo;
 pub mod speed; 
@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::argument,
+        CommandExecutor, CommandSender,
+    },
+    entity::player::Player,
+    server::Server,
+};
+use pumpkin::command::CommandSender::Player as PlayerSender;
+use pumpkin_util::text::TextComponent;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::ignore::is_ignoring;
+use super::mute::{get_mute, muted_message};
+use crate::chat_filter::filter_player_text;
+use super::targets::{player_required, sender_name, PlayersOrNickArgumentConsumer};
+
+const ARG_TARGET: &str = "target";
+const ARG_MESSAGE: &str = "message";
+const SOCIALSPY_PERMISSION: &str = "pumpkin-essentials:command.socialspy";
+
+// /msg command. Takes over every alias of the built-in /msg, so none of them bypass mute or ignore
+const MSG_NAMES: [&str; 3] = ["msg", "tell", "w"];
+const MSG_DESCRIPTION: &str = "Send a private message to a player.";
+
+// /r command
+const REPLY_NAMES: [&str; 2] = ["r", "reply"];
+const REPLY_DESCRIPTION: &str = "Reply to your last private message.";
+
+// /socialspy command
+const SOCIALSPY_NAMES: [&str; 1] = ["socialspy"];
+const SOCIALSPY_DESCRIPTION: &str = "Toggle seeing other players' private messages.";
+
+// One side of a private conversation, None is the console
+type Partner = Option<Uuid>;
+
+// Last conversation partner of each player (and the console), used by /r
+static LAST_PARTNERS: Lazy<Arc<Mutex<HashMap<Partner, Partner>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Players with social spy enabled
+static SOCIAL_SPIES: Lazy<Arc<Mutex<HashSet<Uuid>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashSet::new())));
+
+fn partner_of(sender: &CommandSender) -> Partner {
+    match sender {
+        PlayerSender(player) => Some(player.gameprofile.id),
+        _ => None,
+    }
+}
+
+// Deliver a private message and remember both sides for /r
+async fn send_private_message(
+    server: &Server,
+    sender: &CommandSender,
+    recipient: Option<&Arc<Player>>,
+    message: &str,
+) {
+    let sender_partner = partner_of(sender);
+    let recipient_name = recipient.map_or("Console".to_string(), |player| player.gameprofile.name.clone());
+
+    // Ignored players can't message, the console always can
+    if let (Some(sender_uuid), Some(recipient)) = (sender_partner, recipient) {
+        if is_ignoring(recipient.gameprofile.id, sender_uuid).await {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "{} is not accepting messages from you",
+                    recipient_name
+                )))
+                .await;
+            return;
+        }
+    }
+
+    // Muted players can't send private messages
+    if let PlayerSender(player) = sender {
+        if let Some(mute) = get_mute(player.gameprofile.id).await {
+            sender.send_message(TextComponent::text(muted_message(&mute))).await;
+            return;
+        }
+    }
+
+    // Player messages go through the chat filter, the console is trusted
+    let message = match sender {
+        PlayerSender(player) => {
+            match filter_player_text(server, player, "private message", message).await {
+                Some(message) => message,
+                None => return,
+            }
+        }
+        _ => message.to_string(),
+    };
+
+    let name = sender_name(sender);
+    sender
+        .send_message(TextComponent::text(format!("[me -> {}] {}", recipient_name, message)))
+        .await;
+    match recipient {
+        Some(recipient) => {
+            recipient
+                .send_system_message(&TextComponent::text(format!("[{} -> me] {}", name, message)))
+                .await;
+        }
+        None => log::info!("[{} -> me] {}", name, message),
+    }
+
+    let recipient_partner = recipient.map(|player| player.gameprofile.id);
+    {
+        let mut partners = LAST_PARTNERS.lock().await;
+        partners.insert(sender_partner, recipient_partner);
+        partners.insert(recipient_partner, sender_partner);
+    }
+
+    // Show the message to social spies that are not part of the conversation
+    let spies: Vec<Uuid> = SOCIAL_SPIES.lock().await.iter().copied().collect();
+    let spy_message = TextComponent::text(format!("[Spy] [{} -> {}] {}", name, recipient_name, message));
+    for spy_uuid in spies {
+        if Some(spy_uuid) == sender_partner || Some(spy_uuid) == recipient_partner {
+            continue;
+        }
+        if let Some(spy) = server.get_player_by_uuid(spy_uuid).await {
+            if spy.has_permission(SOCIALSPY_PERMISSION).await {
+                spy.send_system_message(&spy_message).await;
+            }
+        }
+    }
+}
+
+struct MsgExecutor;
+
+#[async_trait]
+impl CommandExecutor for MsgExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
+            if players.len() == 1 {
+                players[0].clone()
+            } else {
+                return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+            }
+        } else {
+            return Err(InvalidConsumption(Some("Target player is required".to_string())));
+        };
+
+        let Some(Arg::Msg(message)) = args.get(ARG_MESSAGE) else {
+            return Err(InvalidConsumption(Some("Message is required".to_string())));
+        };
+
+        if partner_of(sender) == Some(target_player.gameprofile.id) {
+            sender
+                .send_message(TextComponent::text("You cannot message yourself"))
+                .await;
+            return Ok(());
+        }
+
+        send_private_message(server, sender, Some(&target_player), message).await;
+
+        Ok(())
+    }
+}
+
+struct ReplyExecutor;
+
+#[async_trait]
+impl CommandExecutor for ReplyExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(Arg::Msg(message)) = args.get(ARG_MESSAGE) else {
+            return Err(InvalidConsumption(Some("Message is required".to_string())));
+        };
+
+        let last_partner = LAST_PARTNERS.lock().await.get(&partner_of(sender)).copied();
+        match last_partner {
+            None => {
+                sender
+                    .send_message(TextComponent::text("You have nobody to reply to"))
+                    .await;
+            }
+            // Replying to the console
+            Some(None) => send_private_message(server, sender, None, message).await,
+            Some(Some(partner_uuid)) => match server.get_player_by_uuid(partner_uuid).await {
+                Some(partner) => send_private_message(server, sender, Some(&partner), message).await,
+                None => {
+                    sender
+                        .send_message(TextComponent::text("That player is no longer online"))
+                        .await;
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+struct SocialspyExecutor;
+
+#[async_trait]
+impl CommandExecutor for SocialspyExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let PlayerSender(target) = sender {
+            let enabled = {
+                let mut spies = SOCIAL_SPIES.lock().await;
+                if spies.remove(&target.gameprofile.id) {
+                    false
+                } else {
+                    spies.insert(target.gameprofile.id);
+                    true
+                }
+            };
+
+            target
+                .send_system_message(&TextComponent::text(format!(
+                    "Social spy {}",
+                    if enabled { "enabled" } else { "disabled" }
+                )))
+                .await;
+
+            Ok(())
+        } else {
+            Err(player_required())
+        }
+    }
+}
+
+pub fn init_msg_command_tree() -> CommandTree {
+    CommandTree::new(MSG_NAMES, MSG_DESCRIPTION).then(
+        argument(ARG_TARGET, PlayersOrNickArgumentConsumer)
+            .then(argument(ARG_MESSAGE, MsgArgConsumer).execute(MsgExecutor))
+    )
+}
+
+pub fn init_reply_command_tree() -> CommandTree {
+    CommandTree::new(REPLY_NAMES, REPLY_DESCRIPTION)
+        .then(argument(ARG_MESSAGE, MsgArgConsumer).execute(ReplyExecutor))
+}
+
+pub fn init_socialspy_command_tree() -> CommandTree {
+    CommandTree::new(SOCIALSPY_NAMES, SOCIALSPY_DESCRIPTION).execute(SocialspyExecutor)
+}
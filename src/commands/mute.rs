@@ -0,0 +1,369 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer, simple::SimpleArgConsumer},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::argument,
+        CommandExecutor, CommandSender,
+    },
+    plugin::{
+        player::{player_chat::PlayerChatEvent, player_command_send::PlayerCommandSendEvent},
+        Cancellable, EventHandler,
+    },
+    server::Server,
+};
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::targets::sender_name;
+use crate::player_state::find_player_uuid;
+use crate::{config, storage};
+
+// /mute command
+const MUTE_NAMES: [&str; 1] = ["mute"];
+const MUTE_DESCRIPTION: &str = "Stop a player from chatting.";
+
+// /unmute command
+const UNMUTE_NAMES: [&str; 1] = ["unmute"];
+const UNMUTE_DESCRIPTION: &str = "Let a muted player chat again.";
+
+const ARG_TARGET: &str = "target";
+const ARG_DURATION: &str = "duration";
+const ARG_REASON: &str = "reason";
+const MUTES_FILE: &str = "mutes.yml";
+// Longest mute duration accepted, 10 years
+const MAX_DURATION: u64 = 10 * 365 * 24 * 60 * 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mute {
+    pub player_name: String,
+    pub issuer: String,
+    pub reason: Option<String>,
+    // Unix time in seconds when the mute ends, None for a permanent mute
+    pub expires_at: Option<u64>,
+}
+
+// Active mutes per player
+static MUTES: Lazy<Arc<Mutex<HashMap<Uuid, Mute>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+// Parse a duration like 30s, 10m, 2h, 7d or 1w into seconds. None when the input doesn't
+// start with a digit and belongs to the reason, an error when it starts with one but isn't a
+// valid duration or is zero or longer than MAX_DURATION
+fn parse_duration(input: &str) -> Result<Option<u64>, String> {
+    if !input.starts_with(|c: char| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let invalid = || format!("Invalid duration '{}', use a number followed by s, m, h, d or w", input);
+
+    // Unitless numbers and compound durations like 1h30m are refused
+    let split = input.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = input.split_at(split);
+    let multiplier: u64 = match unit.to_lowercase().as_str() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    match amount.parse::<u64>().ok().and_then(|amount| amount.checked_mul(multiplier)) {
+        Some(0) => Err("A mute duration must be longer than zero".to_string()),
+        Some(seconds) if seconds <= MAX_DURATION => Ok(Some(seconds)),
+        _ => Err("A mute can last at most 10 years, leave the duration out for a permanent one".to_string()),
+    }
+}
+
+// Format a number of seconds as the largest units, e.g. 2d 3h or 5m 10s
+fn format_duration(seconds: u64) -> String {
+    let units = [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60), ("s", 1)];
+    let parts: Vec<String> = units
+        .iter()
+        .scan(seconds, |left, (name, size)| {
+            let amount = *left / size;
+            *left %= size;
+            Some((amount, name))
+        })
+        .filter(|(amount, _)| *amount > 0)
+        .take(2)
+        .map(|(amount, name)| format!("{}{}", amount, name))
+        .collect();
+    if parts.is_empty() { "0s".to_string() } else { parts.join(" ") }
+}
+
+// Load saved mutes from disk
+pub async fn load() {
+    let mutes: HashMap<Uuid, Mute> = storage::load_yaml(MUTES_FILE);
+    *MUTES.lock().await = mutes;
+}
+
+// Helper function to get a player's active mute, removing it once expired
+pub async fn get_mute(player_uuid: Uuid) -> Option<Mute> {
+    let mut mutes = MUTES.lock().await;
+    let mute = mutes.get(&player_uuid)?;
+    if mute.expires_at.is_some_and(|expires_at| expires_at <= now_seconds()) {
+        mutes.remove(&player_uuid);
+        storage::save_yaml(MUTES_FILE, &*mutes);
+        return None;
+    }
+    Some(mute.clone())
+}
+
+// Message shown to a muted player, with the time left and the reason
+pub fn muted_message(mute: &Mute) -> String {
+    let duration = match mute.expires_at {
+        Some(expires_at) => format!("for {}", format_duration(expires_at.saturating_sub(now_seconds()))),
+        None => "permanently".to_string(),
+    };
+    match &mute.reason {
+        Some(reason) => format!("You are muted {} ({})", duration, reason),
+        None => format!("You are muted {}", duration),
+    }
+}
+
+struct MuteExecutor;
+
+#[async_trait]
+impl CommandExecutor for MuteExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(Arg::Simple(target_name)) = args.get(ARG_TARGET) else {
+            return Err(InvalidConsumption(Some("Target player is required".to_string())));
+        };
+
+        // Offline players can be muted as long as they joined once
+        let Some((player_uuid, player_name)) = find_player_uuid(server, target_name).await else {
+            sender
+                .send_message(TextComponent::text(format!("Player '{}' has never joined", target_name)))
+                .await;
+            return Ok(());
+        };
+
+        // The duration is optional, a first word that isn't one starts the reason
+        let mut duration = None;
+        let mut reason_parts = Vec::new();
+        if let Some(Arg::Simple(first)) = args.get(ARG_DURATION) {
+            match parse_duration(first).map_err(|message| InvalidConsumption(Some(message)))? {
+                Some(seconds) => duration = Some(seconds),
+                None => reason_parts.push(first.to_string()),
+            }
+        }
+        if let Some(Arg::Msg(rest)) = args.get(ARG_REASON) {
+            reason_parts.push(rest.clone());
+        }
+        let reason = Some(reason_parts.join(" ")).filter(|reason| !reason.is_empty());
+
+        let mute = Mute {
+            player_name: player_name.clone(),
+            issuer: sender_name(sender),
+            reason,
+            expires_at: duration.map(|seconds| now_seconds().saturating_add(seconds)),
+        };
+
+        {
+            let mut mutes = MUTES.lock().await;
+            mutes.insert(player_uuid, mute.clone());
+            storage::save_yaml(MUTES_FILE, &*mutes);
+        }
+
+        let duration_text = duration.map_or("permanently".to_string(), |seconds| {
+            format!("for {}", format_duration(seconds))
+        });
+        log::info!(
+            "[MUTE] {} muted {} {}{}",
+            mute.issuer,
+            player_name,
+            duration_text,
+            mute.reason.as_ref().map_or(String::new(), |reason| format!(" ({})", reason))
+        );
+
+        if let Some(target_player) = server.get_player_by_uuid(player_uuid).await {
+            target_player
+                .send_system_message(&TextComponent::text(muted_message(&mute)))
+                .await;
+        }
+        sender
+            .send_message(TextComponent::text(format!("Muted {} {}", player_name, duration_text)))
+            .await;
+
+        Ok(())
+    }
+}
+
+struct UnmuteExecutor;
+
+#[async_trait]
+impl CommandExecutor for UnmuteExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(Arg::Simple(target_name)) = args.get(ARG_TARGET) else {
+            return Err(InvalidConsumption(Some("Target player is required".to_string())));
+        };
+
+        let Some((player_uuid, player_name)) = find_player_uuid(server, target_name).await else {
+            sender
+                .send_message(TextComponent::text(format!("Player '{}' has never joined", target_name)))
+                .await;
+            return Ok(());
+        };
+
+        let removed = {
+            let mut mutes = MUTES.lock().await;
+            let removed = mutes.remove(&player_uuid).is_some();
+            if removed {
+                storage::save_yaml(MUTES_FILE, &*mutes);
+            }
+            removed
+        };
+
+        if !removed {
+            sender
+                .send_message(TextComponent::text(format!("{} is not muted", player_name)))
+                .await;
+            return Ok(());
+        }
+
+        log::info!("[MUTE] {} unmuted {}", sender_name(sender), player_name);
+        if let Some(target_player) = server.get_player_by_uuid(player_uuid).await {
+            target_player
+                .send_system_message(&TextComponent::text("You are no longer muted"))
+                .await;
+        }
+        sender
+            .send_message(TextComponent::text(format!("Unmuted {}", player_name)))
+            .await;
+
+        Ok(())
+    }
+}
+
+// Event handler for PlayerChatEvent blocking chat from muted players
+pub struct MuteChatHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerChatEvent> for MuteChatHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerChatEvent) {
+        if let Some(mute) = get_mute(event.player.gameprofile.id).await {
+            event.set_cancelled(true);
+            event
+                .player
+                .send_system_message(&TextComponent::text(muted_message(&mute)))
+                .await;
+        }
+    }
+}
+
+// Event handler for PlayerCommandSendEvent blocking the configured commands while muted
+pub struct MuteCommandHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerCommandSendEvent> for MuteCommandHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerCommandSendEvent) {
+        let command = event.command.trim_start_matches('/').to_lowercase();
+        let blocked = config::get().mute.blocked_commands.iter().any(|blocked| {
+            let blocked = blocked.trim_start_matches('/').to_lowercase();
+            command == blocked || command.starts_with(&format!("{} ", blocked))
+        });
+        if !blocked {
+            return;
+        }
+
+        if let Some(mute) = get_mute(event.player.gameprofile.id).await {
+            event.set_cancelled(true);
+            event
+                .player
+                .send_system_message(&TextComponent::text(muted_message(&mute)))
+                .await;
+        }
+    }
+}
+
+pub fn init_mute_command_tree() -> CommandTree {
+    CommandTree::new(MUTE_NAMES, MUTE_DESCRIPTION).then(
+        argument(ARG_TARGET, SimpleArgConsumer)
+            .execute(MuteExecutor)
+            .then(
+                argument(ARG_DURATION, SimpleArgConsumer)
+                    .execute(MuteExecutor)
+                    .then(argument(ARG_REASON, MsgArgConsumer).execute(MuteExecutor))
+            )
+    )
+}
+
+pub fn init_unmute_command_tree() -> CommandTree {
+    CommandTree::new(UNMUTE_NAMES, UNMUTE_DESCRIPTION)
+        .then(argument(ARG_TARGET, SimpleArgConsumer).execute(UnmuteExecutor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_reads_every_unit() {
+        assert_eq!(parse_duration("30s"), Ok(Some(30)));
+        assert_eq!(parse_duration("10M"), Ok(Some(600)));
+        assert_eq!(parse_duration("2h"), Ok(Some(2 * 60 * 60)));
+        assert_eq!(parse_duration("7d"), Ok(Some(7 * 24 * 60 * 60)));
+        assert_eq!(parse_duration("1w"), Ok(Some(7 * 24 * 60 * 60)));
+    }
+
+    #[test]
+    fn parse_duration_leaves_other_words_to_the_reason() {
+        assert_eq!(parse_duration("spam"), Ok(None));
+        assert_eq!(parse_duration("m"), Ok(None));
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_durations() {
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("1h30m").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("0").is_err());
+    }
+
+    #[test]
+    fn parse_duration_rejects_durations_over_the_limit() {
+        assert_eq!(parse_duration("3650d"), Ok(Some(MAX_DURATION)));
+        assert!(parse_duration("3651d").is_err());
+        assert!(parse_duration("99999999999999999999w").is_err());
+        assert!(parse_duration(&format!("{}w", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn format_duration_shows_the_two_largest_units() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60 * 60), "1h");
+        assert_eq!(format_duration(60 * 60 + 61), "1h 1m");
+        assert_eq!(format_duration(24 * 60 * 60 + 5), "1d 5s");
+        assert_eq!(format_duration(MAX_DURATION), "3650d");
+    }
+}
@@ -0,0 +1,228 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use pumpkin::{
+    command::{
+        args::{Arg, ConsumedArgs, simple::SimpleArgConsumer},
+        dispatcher::CommandError,
+        dispatcher::CommandError::InvalidConsumption,
+        tree::CommandTree,
+        tree::builder::{argument, literal},
+        CommandExecutor, CommandSender,
+    },
+    entity::player::Player,
+    plugin::{player::player_join::PlayerJoinEvent, EventHandler},
+    server::Server,
+};
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::targets::{authorize_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer};
+use crate::player_state::find_player_uuid;
+use crate::storage;
+use crate::text_format::{filter_codes, parse_codes, strip_codes};
+
+const NAMES: [&str; 1] = ["nick"];
+const DESCRIPTION: &str = "Set a nickname for yourself or another player.";
+const ARG_NICK: &str = "nickname";
+const ARG_TARGET: &str = "target";
+const COLOR_PERMISSION: &str = "pumpkin-essentials:command.nick.color";
+const FORMAT_PERMISSION: &str = "pumpkin-essentials:command.nick.format";
+const NICKNAMES_FILE: &str = "nicknames.yml";
+
+// Nicknames per player, stored with their & codes
+static NICKNAMES: Lazy<Arc<Mutex<HashMap<Uuid, String>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Load saved nicknames from disk
+pub async fn load() {
+    let nicknames: HashMap<Uuid, String> = storage::load_yaml(NICKNAMES_FILE);
+    *NICKNAMES.lock().await = nicknames;
+}
+
+// Name shown for a player in chat, the nickname with its codes or the real name
+pub async fn display_name(player: &Player) -> String {
+    NICKNAMES
+        .lock()
+        .await
+        .get(&player.gameprofile.id)
+        .cloned()
+        .unwrap_or_else(|| player.gameprofile.name.clone())
+}
+
+// Helper function to find an online player by nickname, ignoring codes and case
+pub async fn find_by_nickname(server: &Server, name: &str) -> Option<Arc<Player>> {
+    let uuid = {
+        let nicknames = NICKNAMES.lock().await;
+        nicknames
+            .iter()
+            .find(|(_, nickname)| strip_codes(nickname).eq_ignore_ascii_case(name))
+            .map(|(uuid, _)| *uuid)?
+    };
+    server.get_player_by_uuid(uuid).await
+}
+
+// Show the nickname in the tab list and above the player
+async fn apply_nickname(player: &Player) {
+    let nickname = NICKNAMES.lock().await.get(&player.gameprofile.id).cloned();
+    player.set_display_name(nickname.as_deref().map(parse_codes)).await;
+}
+
+// Check a nickname is a valid name that doesn't copy another player, returns the reason otherwise
+async fn validate_nickname(server: &Server, player: &Player, nickname: &str) -> Result<(), String> {
+    let plain = strip_codes(nickname);
+    if !(3..=16).contains(&plain.chars().count())
+        || !plain.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err("Nicknames must be 3 to 16 letters, digits or underscores".to_string());
+    }
+
+    // Coloring your own name is fine, taking someone else's is not
+    if plain.eq_ignore_ascii_case(&player.gameprofile.name) {
+        return Ok(());
+    }
+    if let Some((uuid, _)) = find_player_uuid(server, &plain).await {
+        if uuid != player.gameprofile.id {
+            return Err(format!("'{}' is the name of another player", plain));
+        }
+    }
+
+    let nicknames = NICKNAMES.lock().await;
+    let taken = nicknames.iter().any(|(uuid, other)| {
+        *uuid != player.gameprofile.id && strip_codes(other).eq_ignore_ascii_case(&plain)
+    });
+    if taken {
+        return Err(format!("The nickname '{}' is already taken", plain));
+    }
+    Ok(())
+}
+
+// Set or clear a nickname and save it
+async fn set_nickname(player: &Player, nickname: Option<String>) {
+    {
+        let mut nicknames = NICKNAMES.lock().await;
+        match nickname {
+            Some(nickname) => nicknames.insert(player.gameprofile.id, nickname),
+            None => nicknames.remove(&player.gameprofile.id),
+        };
+        storage::save_yaml(NICKNAMES_FILE, &*nicknames);
+    }
+    apply_nickname(player).await;
+}
+
+// Helper function to get the single player a /nick command applies to
+async fn nick_target(sender: &CommandSender, args: &ConsumedArgs<'_>) -> Result<Option<Arc<Player>>, CommandError> {
+    let target_players = resolve_targets(sender, args, ARG_TARGET)?;
+    if target_players.len() != 1 {
+        return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+    }
+    let target_players = authorize_targets(sender, target_players, "nick").await;
+    Ok(target_players.and_then(|mut target_players| target_players.pop()))
+}
+
+struct NickExecutor;
+
+#[async_trait]
+impl CommandExecutor for NickExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(Arg::Simple(input)) = args.get(ARG_NICK) else {
+            return Err(InvalidConsumption(Some("Nickname is required".to_string())));
+        };
+        let Some(target_player) = nick_target(sender, args).await? else {
+            return Ok(());
+        };
+
+        // Drop the codes the sender may not use
+        let nickname = filter_codes(
+            input,
+            sender.has_permission(COLOR_PERMISSION).await,
+            sender.has_permission(FORMAT_PERMISSION).await,
+        );
+        if let Err(message) = validate_nickname(server, &target_player, &nickname).await {
+            sender.send_message(TextComponent::text(message)).await;
+            return Ok(());
+        }
+
+        set_nickname(&target_player, Some(nickname.clone())).await;
+
+        target_player
+            .send_system_message(&TextComponent::text("Your nickname is now ").add_child(parse_codes(&nickname)))
+            .await;
+        if !is_self(sender, &target_player) {
+            sender
+                .send_message(
+                    TextComponent::text(format!("{}'s nickname is now ", target_player.gameprofile.name))
+                        .add_child(parse_codes(&nickname)),
+                )
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+// /nick off command
+struct NickOffExecutor;
+
+#[async_trait]
+impl CommandExecutor for NickOffExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(target_player) = nick_target(sender, args).await? else {
+            return Ok(());
+        };
+
+        set_nickname(&target_player, None).await;
+
+        target_player
+            .send_system_message(&TextComponent::text("Your nickname was removed"))
+            .await;
+        if !is_self(sender, &target_player) {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Removed {}'s nickname",
+                    target_player.gameprofile.name
+                )))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+// Event handler for PlayerJoinEvent showing the saved nickname
+pub struct NickJoinHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerJoinEvent> for NickJoinHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerJoinEvent) {
+        apply_nickname(&event.player).await;
+    }
+}
+
+pub fn init_command_tree() -> CommandTree {
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(
+            literal("off")
+                .execute(NickOffExecutor)
+                .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(NickOffExecutor))
+        )
+        .then(
+            argument(ARG_NICK, SimpleArgConsumer)
+                .execute(NickExecutor)
+                .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(NickExecutor))
+        )
+}
@@ -1,23 +1,45 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::{Arg, ConsumedArgs},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::{argument, literal},
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Instant;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use uuid::Uuid;
+
+use super::targets::{authorize_targets, is_self, player_required, PlayersOrNickArgumentConsumer};
 
 const NAMES: [&str; 1] = ["ping"];
 const DESCRIPTION: &str = "Check ping for yourself or another player.";
 const ARG_TARGET: &str = "target";
 
+// Keep-alive samples kept per player
+const HISTORY_SIZE: usize = 10;
+// Pumpkin sends a keep-alive every 15 seconds and has no event for the answer. The keep-alive
+// state is checked every millisecond from shortly before one is due until it is answered,
+// and every 50ms the rest of the time
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const WAKE_EARLY: Duration = Duration::from_millis(200);
+const PRECISE_INTERVAL: Duration = Duration::from_millis(1);
+const IDLE_INTERVAL: Duration = Duration::from_millis(50);
+// Players listed by /ping top
+const TOP_COUNT: usize = 10;
+
 struct PingExecutor;
 
 #[async_trait]
@@ -28,72 +50,191 @@ impl CommandExecutor for PingExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
+        let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
+            if players.len() == 1 {
+                players[0].clone()
             } else {
-                target.clone()
-            };
-
-            // Calculate ping based on keep-alive timing
-            let ping_ms = calculate_ping(&target_player).await;
-            let player_name = &target_player.gameprofile.name;
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "Your ping: {}ms",
-                        ping_ms
-                    )))
-                    .await;
-            } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "{}'s ping: {}ms",
-                        player_name, ping_ms
-                    )))
-                    .await;
+                return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
             }
+        } else if let Player(target) = sender {
+            target.clone()
+        } else {
+            return Err(player_required());
+        };
 
-            Ok(())
+        if authorize_targets(sender, vec![target_player.clone()], "ping").await.is_none() {
+            return Ok(());
+        }
+
+        let player_name = &target_player.gameprofile.name;
+        let Some(stats) = latency_stats(target_player.gameprofile.id).await else {
+            sender
+                .send_message(TextComponent::text(if is_self(sender, &target_player) {
+                    "Your ping hasn't been measured yet, try again in a few seconds".to_string()
+                } else {
+                    format!("{}'s ping hasn't been measured yet, try again in a few seconds", player_name)
+                }))
+                .await;
+            return Ok(());
+        };
+
+        let prefix = if is_self(sender, &target_player) {
+            "Your ping".to_string()
         } else {
-            Err(InvalidRequirement)
+            format!("{}'s ping", player_name)
+        };
+        sender
+            .send_message(TextComponent::text(format!(
+                "{}: {}ms (average {}ms, jitter {}ms over {} samples)",
+                prefix, stats.current, stats.average, stats.jitter, stats.samples
+            )))
+            .await;
+
+        Ok(())
+    }
+}
+
+// /ping top command
+struct PingTopExecutor;
+
+#[async_trait]
+impl CommandExecutor for PingTopExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let mut measured = Vec::new();
+        for player in server.get_all_players().await {
+            if let Some(stats) = latency_stats(player.gameprofile.id).await {
+                measured.push((player.gameprofile.name.clone(), stats));
+            }
+        }
+
+        if measured.is_empty() {
+            sender
+                .send_message(TextComponent::text("No ping has been measured yet"))
+                .await;
+            return Ok(());
         }
+
+        // Worst connections first, by average so a single spike doesn't dominate
+        measured.sort_by(|a, b| b.1.average.cmp(&a.1.average));
+        measured.truncate(TOP_COUNT);
+
+        sender
+            .send_message(TextComponent::text(format!("Highest ping ({} players):", measured.len())))
+            .await;
+        for (rank, (name, stats)) in measured.iter().enumerate() {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "{}. {}: {}ms average, {}ms current, {}ms jitter",
+                    rank + 1, name, stats.average, stats.current, stats.jitter
+                )))
+                .await;
+        }
+
+        Ok(())
     }
 }
 
-/// Calculate ping based on keep-alive timing
-async fn calculate_ping(player: &pumpkin::entity::player::Player) -> u64 {
-    let now = Instant::now();
-    let last_keep_alive_time = player.last_keep_alive_time.load();
-    let waiting = player.wait_for_keep_alive.load(std::sync::atomic::Ordering::Relaxed);
-    
-    if waiting {
-        // If we're waiting for a keep-alive response, calculate based on time since last sent
-        let elapsed = now.duration_since(last_keep_alive_time);
-        elapsed.as_millis() as u64
-    } else {
-        // If not waiting, use a base ping or calculate from last response
-        // For now, we'll use a simple calculation based on connection time
-        let connection_time = player.tick_counter.load(std::sync::atomic::Ordering::Relaxed);
-        if connection_time > 0 {
-            // Simple heuristic: newer connections might have higher ping
-            (100 + (connection_time as u64 % 50)).min(500)
-        } else {
-            100 // Default ping
+// Rolling keep-alive round trips for one player
+#[derive(Default)]
+struct LatencyHistory {
+    // When the last keep-alive seen was sent, and whether its answer is still owed
+    last_sent: Option<Instant>,
+    pending: bool,
+    samples: VecDeque<u64>,
+}
+
+impl LatencyHistory {
+    fn record(&mut self, round_trip: Duration) {
+        if self.samples.len() == HISTORY_SIZE {
+            self.samples.pop_front();
         }
+        self.samples.push_back(round_trip.as_millis() as u64);
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
+pub struct LatencyStats {
+    pub current: u64,
+    pub average: u64,
+    pub jitter: u64,
+    pub samples: usize,
+}
+
+static LATENCY_HISTORY: Lazy<Arc<Mutex<HashMap<Uuid, LatencyHistory>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Current, average and jitter (mean difference between consecutive samples) for a player
+pub async fn latency_stats(player_uuid: Uuid) -> Option<LatencyStats> {
+    let history = LATENCY_HISTORY.lock().await;
+    let samples = &history.get(&player_uuid)?.samples;
+    let current = *samples.back()?;
+
+    let average = samples.iter().sum::<u64>() / samples.len() as u64;
+    let jitter = if samples.len() > 1 {
+        samples
+            .iter()
+            .zip(samples.iter().skip(1))
+            .map(|(a, b)| a.abs_diff(*b))
+            .sum::<u64>()
+            / (samples.len() as u64 - 1)
+    } else {
+        0
+    };
+
+    Some(LatencyStats { current, average, jitter, samples: samples.len() })
+}
+
+// Watch each player's keep-alive state and record the round trip when the answer arrives.
+// The server sends a keep-alive and sets wait_for_keep_alive, the client's answer clears it.
+// The returned task is aborted when the plugin unloads
+pub fn start_latency_task(server: Arc<Server>) -> JoinHandle<()> {
+    crate::TOKIO_RUNTIME.spawn(async move {
+        let mut precise = false;
+        loop {
+            let players = server.get_all_players().await;
+            let mut history = LATENCY_HISTORY.lock().await;
+
+            // Forget players who left
+            history.retain(|uuid, _| players.iter().any(|player| player.gameprofile.id == *uuid));
+
+            let mut watch_closely = false;
+            for player in &players {
+                let entry = history.entry(player.gameprofile.id).or_default();
+                let sent_at = player.last_keep_alive_time.load();
+                let waiting = player.wait_for_keep_alive.load(Ordering::Relaxed);
+
+                if entry.last_sent != Some(sent_at) {
+                    // A keep-alive went out since the last check. When it is already answered
+                    // the answer came in since that check too, which only gives a usable
+                    // sample when the check was a millisecond ago
+                    let first_seen = entry.last_sent.is_none();
+                    entry.last_sent = Some(sent_at);
+                    entry.pending = waiting;
+                    if !waiting && precise && !first_seen {
+                        entry.record(sent_at.elapsed());
+                    }
+                } else if entry.pending && !waiting {
+                    entry.pending = false;
+                    entry.record(sent_at.elapsed());
+                }
+
+                watch_closely |= entry.pending || sent_at.elapsed() + WAKE_EARLY >= KEEP_ALIVE_INTERVAL;
+            }
+            drop(history);
+
+            precise = watch_closely;
+            sleep(if watch_closely { PRECISE_INTERVAL } else { IDLE_INTERVAL }).await;
+        }
+    })
+}
+
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .execute(PingExecutor)
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(PingExecutor))
-    )
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(PingExecutor)
+        .then(literal("top").execute(PingTopExecutor))
+        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(PingExecutor))
 }
\ No newline at end of file
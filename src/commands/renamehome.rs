@@ -2,9 +2,9 @@ use async_trait::async_trait;
 use pumpkin::command::args::simple::SimpleArgConsumer;
 use pumpkin::command::args::{Arg, ConsumedArgs};
 use pumpkin::command::dispatcher::CommandError;
-use pumpkin::command::dispatcher::CommandError::{InvalidConsumption, InvalidRequirement};
+use pumpkin::command::dispatcher::CommandError::InvalidConsumption;
 use pumpkin::command::tree::CommandTree;
-use pumpkin::command::tree::builder::{argument, require};
+use pumpkin::command::tree::builder::argument;
 use pumpkin::command::{CommandExecutor, CommandSender};
 use pumpkin::command::CommandSender::Player;
 use pumpkin::server::Server;
@@ -12,6 +12,7 @@ use pumpkin_util::text::TextComponent;
 
 // Import the global PLAYER_HOMES from home_common.rs
 use super::home_common::PLAYER_HOMES;
+use super::targets::player_required;
 
 const NAMES: [&str; 2] = ["renamehome", "rhome"];
 const DESCRIPTION: &str = "Rename an existing home.";
@@ -87,16 +88,14 @@ impl CommandExecutor for RenameHomeExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
     CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_OLD_NAME, SimpleArgConsumer)
-                .then(argument(ARG_NEW_NAME, SimpleArgConsumer).execute(RenameHomeExecutor)))
+        argument(ARG_OLD_NAME, SimpleArgConsumer)
+            .then(argument(ARG_NEW_NAME, SimpleArgConsumer).execute(RenameHomeExecutor))
     )
 } 
\ No newline at end of file
@@ -3,18 +3,59 @@ use pumpkin::{
     command::{
         args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::require,
+        tree::builder::literal,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_data::data_component_impl::{DamageImpl, EnchantmentsImpl};
+use pumpkin_world::inventory::Inventory;
+use pumpkin_world::item::ItemStack;
 use pumpkin_util::text::TextComponent;
 
+use super::targets::player_required;
+
 const NAMES: [&str; 1] = ["repair"];
 const DESCRIPTION: &str = "Repair the item in your hand.";
+const ALL_PERMISSION: &str = "pumpkin-essentials:command.repair.all";
+const ENCHANTED_PERMISSION: &str = "pumpkin-essentials:command.repair.enchanted";
+
+// Why an item was left alone
+enum RepairResult {
+    Repaired,
+    NotDamaged,
+    Blacklisted,
+    Enchanted,
+}
+
+// Helper function to check if an item id is in the repair blacklist
+fn is_blacklisted(stack: &ItemStack) -> bool {
+    let item_id = stack.item.registry_key;
+    crate::config::get().repair.blacklist.iter().any(|entry| {
+        entry.strip_prefix("minecraft:").unwrap_or(entry) == item_id
+    })
+}
+
+// Reset the damage component of a damageable item
+fn repair_stack(stack: &mut ItemStack, allow_enchanted: bool) -> RepairResult {
+    let damaged = stack
+        .get_data_component::<DamageImpl>()
+        .is_some_and(|damage| damage.damage > 0);
+    if stack.is_empty() || !damaged {
+        return RepairResult::NotDamaged;
+    }
+    if is_blacklisted(stack) {
+        return RepairResult::Blacklisted;
+    }
+    if !allow_enchanted && stack.get_data_component::<EnchantmentsImpl>().is_some() {
+        return RepairResult::Enchanted;
+    }
+
+    stack.set_damage(0);
+    RepairResult::Repaired
+}
 
 struct RepairExecutor;
 
@@ -27,24 +68,89 @@ impl CommandExecutor for RepairExecutor {
         _args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
         if let Player(target) = sender {
-            // TODO: Implement item repair functionality
-            // This would require access to the player's inventory and item durability system
-            // For now, we'll show a placeholder message
-            
+            let allow_enchanted = target.has_permission(ENCHANTED_PERMISSION).await;
+            let held_item = target.inventory().held_item();
+            let result = repair_stack(&mut *held_item.lock().await, allow_enchanted);
+
+            let message = match result {
+                RepairResult::Repaired => "Repaired the item in your hand",
+                RepairResult::NotDamaged => "The item in your hand doesn't need repairing",
+                RepairResult::Blacklisted => "This item can't be repaired",
+                RepairResult::Enchanted => "You don't have permission to repair enchanted items",
+            };
+            if matches!(result, RepairResult::Repaired) {
+                target.sync_inventory().await;
+            }
             target
-                .send_system_message(&TextComponent::text("Item repair functionality is not yet implemented."))
+                .send_system_message(&TextComponent::text(message))
                 .await;
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
+        }
+    }
+}
+
+// /repair all command
+struct RepairAllExecutor;
+
+#[async_trait]
+impl CommandExecutor for RepairAllExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        if let Player(target) = sender {
+            if !target.has_permission(ALL_PERMISSION).await {
+                target
+                    .send_system_message(&TextComponent::text("You don't have permission to repair your whole inventory"))
+                    .await;
+                return Ok(());
+            }
+
+            let allow_enchanted = target.has_permission(ENCHANTED_PERMISSION).await;
+            let inventory = target.inventory();
+            let mut repaired_count = 0;
+            let mut skipped_count = 0;
+
+            // Main inventory, hotbar, armor and offhand slots
+            for slot in 0..inventory.size() {
+                let stack = inventory.get_stack(slot).await;
+                match repair_stack(&mut *stack.lock().await, allow_enchanted) {
+                    RepairResult::Repaired => repaired_count += 1,
+                    RepairResult::Blacklisted | RepairResult::Enchanted => skipped_count += 1,
+                    RepairResult::NotDamaged => {}
+                }
+            }
+
+            if repaired_count > 0 {
+                target.sync_inventory().await;
+            }
+
+            let message = match (repaired_count, skipped_count) {
+                (0, 0) => "Nothing in your inventory needs repairing".to_string(),
+                (repaired, 0) => format!("Repaired {} items", repaired),
+                (repaired, skipped) => format!(
+                    "Repaired {} items ({} blacklisted or enchanted items skipped)",
+                    repaired, skipped
+                ),
+            };
+            target
+                .send_system_message(&TextComponent::text(message))
+                .await;
+
+            Ok(())
+        } else {
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(RepairExecutor)
-    )
-}
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(RepairExecutor)
+        .then(literal("all").execute(RepairAllExecutor))
+}
@@ -1,17 +1,21 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer, bounded_num::BoundedNumArgumentConsumer, FindArgDefaultName},
+        args::{Arg, ConsumedArgs, bounded_num::BoundedNumArgumentConsumer, FindArgDefaultName},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::{argument, literal},
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
-use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
+use crate::player_state::{set_fly_speed, set_walk_speed};
+
+use super::targets::{
+    authorize_targets, describe_targets, is_self, resolve_targets, PlayersOrNickArgumentConsumer,
+};
 
 const NAMES: [&str; 1] = ["speed"];
 const DESCRIPTION: &str = "Set walk or fly speed for yourself or another player.";
@@ -19,6 +23,10 @@ const ARG_TYPE: &str = "type";
 const ARG_SPEED: &str = "speed";
 const ARG_TARGET: &str = "target";
 
+// Vanilla ability values, /speed 1 gives these back
+const DEFAULT_WALK_SPEED: f32 = 0.1;
+const DEFAULT_FLY_SPEED: f32 = 0.05;
+
 fn speed_consumer() -> BoundedNumArgumentConsumer<f32> {
     BoundedNumArgumentConsumer::<f32>::new()
         .name("speed")
@@ -26,6 +34,29 @@ fn speed_consumer() -> BoundedNumArgumentConsumer<f32> {
         .max(10.0)
 }
 
+// Map the 0-10 scale onto multiples of the vanilla default (1 = vanilla, 10 = ten times faster)
+fn scaled_speed(speed_type: &str, speed: f32) -> f32 {
+    match speed_type {
+        "walk" => DEFAULT_WALK_SPEED * speed,
+        _ => DEFAULT_FLY_SPEED * speed,
+    }
+}
+
+// Highest /speed value the sender may use, raised by speed.max.<n> permissions
+async fn max_speed_for(sender: &CommandSender) -> f32 {
+    let mut max_speed = crate::config::get().speed.default_max;
+    for level in (1..=10).rev() {
+        if level as f32 <= max_speed {
+            break;
+        }
+        if sender.has_permission(&format!("pumpkin-essentials:command.speed.max.{}", level)).await {
+            max_speed = level as f32;
+            break;
+        }
+    }
+    max_speed
+}
+
 struct SpeedExecutor;
 
 #[async_trait]
@@ -36,98 +67,173 @@ impl CommandExecutor for SpeedExecutor {
         _server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(target) = sender {
-            // Get target player
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
-            } else {
-                target.clone()
-            };
+        let max_speed = max_speed_for(sender).await;
 
-            // Get speed type (walk or fly)
-            let speed_type = if let Some(Arg::Simple(type_str)) = args.get(ARG_TYPE) {
-                match type_str.as_ref() {
-                    "walk" => "walk",
-                    "fly" => "fly",
-                    _ => return Err(InvalidConsumption(Some("Speed type must be 'walk' or 'fly'".to_string()))),
-                }
-            } else {
-                return Err(InvalidConsumption(Some("Speed type is required".to_string())));
-            };
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "speed",
+        ).await else {
+            return Ok(());
+        };
+
+        // Get speed type (walk, fly or auto), auto picks from each player's flying state
+        let requested_type = if let Some(Arg::Simple(type_str)) = args.get(ARG_TYPE) {
+            match type_str.as_ref() {
+                "walk" => "walk",
+                "fly" => "fly",
+                "auto" => "auto",
+                _ => return Err(InvalidConsumption(Some("Speed type must be 'walk', 'fly' or 'auto'".to_string()))),
+            }
+        } else {
+            "auto"
+        };
+
+        // Get speed value using the consumer method
+        let speed = match speed_consumer().find_arg_default_name(args) {
+            Ok(Ok(speed)) => speed,
+            _ => return Err(InvalidConsumption(Some("Valid speed value is required".to_string()))),
+        };
 
-            // Get speed value using the consumer method
-            let speed = match speed_consumer().find_arg_default_name(args) {
-                Ok(Ok(speed)) => speed,
-                _ => return Err(InvalidConsumption(Some("Valid speed value is required".to_string()))),
+        if speed > max_speed {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "You can't set a speed higher than {}",
+                    max_speed
+                )))
+                .await;
+            return Ok(());
+        }
+
+        let mut applied_types = Vec::new();
+        for target_player in &target_players {
+            let speed_type = if requested_type == "auto" {
+                if target_player.abilities.lock().await.flying { "fly" } else { "walk" }
+            } else {
+                requested_type
             };
+            if !applied_types.contains(&speed_type) {
+                applied_types.push(speed_type);
+            }
 
             // Apply speed to player
+            let ability_speed = scaled_speed(speed_type, speed);
             {
                 let mut abilities = target_player.abilities.lock().await;
                 match speed_type {
                     "walk" => {
-                        abilities.walk_speed = speed;
+                        abilities.walk_speed = ability_speed;
                     }
                     "fly" => {
-                        abilities.fly_speed = speed;
+                        abilities.fly_speed = ability_speed;
                     }
                     _ => unreachable!(),
                 }
             }
             target_player.send_abilities_update().await;
 
-            let player_name = &target_player.gameprofile.name;
-            let speed_type_display = if speed_type == "walk" { "walk" } else { "fly" };
-            
-            if std::ptr::eq(target, &target_player) {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "{} speed set to {}",
-                        speed_type_display,
-                        speed
-                    )))
-                    .await;
-            } else {
-                target
-                    .send_system_message(&TextComponent::text(format!(
-                        "{} speed set to {} for {}",
-                        speed_type_display,
-                        speed,
-                        player_name
-                    )))
-                    .await;
-                    
-                target_player
-                    .send_system_message(&TextComponent::text(format!(
-                        "{} speed set to {}",
-                        speed_type_display,
-                        speed
-                    )))
-                    .await;
+            // Remember the speed so it is reapplied on join and respawn
+            match speed_type {
+                "walk" => set_walk_speed(target_player.gameprofile.id, Some(ability_speed)).await,
+                "fly" => set_fly_speed(target_player.gameprofile.id, Some(ability_speed)).await,
+                _ => unreachable!(),
             }
 
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+            target_player
+                .send_system_message(&TextComponent::text(format!(
+                    "{} speed set to {}",
+                    if speed_type == "walk" { "Walk" } else { "Fly" },
+                    speed
+                )))
+                .await;
         }
+
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            let speed_type_display = match applied_types.as_slice() {
+                ["walk"] => "Walk",
+                ["fly"] => "Fly",
+                _ => "Walk/fly",
+            };
+            sender
+                .send_message(TextComponent::text(format!(
+                    "{} speed set to {} for {}",
+                    speed_type_display,
+                    speed,
+                    describe_targets(&target_players)
+                )))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+// /speed reset command
+struct SpeedResetExecutor;
+
+#[async_trait]
+impl CommandExecutor for SpeedResetExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        _server: &Server,
+        args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(target_players) = authorize_targets(
+            sender,
+            resolve_targets(sender, args, ARG_TARGET)?,
+            "speed",
+        ).await else {
+            return Ok(());
+        };
+
+        for target_player in &target_players {
+            {
+                let mut abilities = target_player.abilities.lock().await;
+                abilities.walk_speed = DEFAULT_WALK_SPEED;
+                abilities.fly_speed = DEFAULT_FLY_SPEED;
+            }
+            target_player.send_abilities_update().await;
+
+            set_walk_speed(target_player.gameprofile.id, None).await;
+            set_fly_speed(target_player.gameprofile.id, None).await;
+
+            target_player
+                .send_system_message(&TextComponent::text("Walk and fly speed reset"))
+                .await;
+        }
+
+        if !(target_players.len() == 1 && is_self(sender, &target_players[0])) {
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Walk and fly speed reset for {}",
+                    describe_targets(&target_players)
+                )))
+                .await;
+        }
+
+        Ok(())
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(
-                argument(ARG_TYPE, pumpkin::command::args::simple::SimpleArgConsumer)
-                    .then(
-                        argument(ARG_SPEED, speed_consumer())
-                            .execute(SpeedExecutor)
-                            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(SpeedExecutor))
-                    )
-            )
-    )
-} 
\ No newline at end of file
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(
+            literal("reset")
+                .execute(SpeedResetExecutor)
+                .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(SpeedResetExecutor))
+        )
+        .then(
+            argument(ARG_SPEED, speed_consumer())
+                .execute(SpeedExecutor)
+                .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(SpeedExecutor))
+        )
+        .then(
+            argument(ARG_TYPE, pumpkin::command::args::simple::SimpleArgConsumer)
+                .then(
+                    argument(ARG_SPEED, speed_consumer())
+                        .execute(SpeedExecutor)
+                        .then(argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(SpeedExecutor))
+                )
+        )
+}
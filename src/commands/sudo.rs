@@ -1,22 +1,30 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer, message::MsgArgConsumer},
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
+    plugin::{player::player_chat::PlayerChatEvent, Cancellable},
     server::Server,
+    PLUGIN_MANAGER,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_util::PermissionLvl;
 use pumpkin_util::text::TextComponent;
+use std::sync::Arc;
+
+use super::targets::{sender_name, PlayersOrNickArgumentConsumer};
 
 const NAMES: [&str; 1] = ["sudo"];
 const DESCRIPTION: &str = "Execute a command as another player.";
 const ARG_TARGET: &str = "target";
 const ARG_COMMAND: &str = "command";
+const EXEMPT_PERMISSION: &str = "pumpkin-essentials:command.sudo.exempt";
+const ELEVATE_PERMISSION: &str = "pumpkin-essentials:command.sudo.elevate";
 
 struct SudoExecutor;
 
@@ -28,56 +36,144 @@ impl CommandExecutor for SudoExecutor {
         server: &Server,
         args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
-        if let Player(executor) = sender {
-            let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
-                if players.len() == 1 {
-                    players[0].clone()
-                } else {
-                    return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
-                }
-            } else {
-                return Err(InvalidConsumption(Some("Target player is required".to_string())));
-            };
-
-            let command = if let Some(Arg::Msg(cmd)) = args.get(ARG_COMMAND) {
-                cmd.clone()
+        let target_player = if let Some(Arg::Players(players)) = args.get(ARG_TARGET) {
+            if players.len() == 1 {
+                players[0].clone()
             } else {
-                return Err(InvalidConsumption(Some("Command is required".to_string())));
-            };
+                return Err(InvalidConsumption(Some("Expected exactly one player".to_string())));
+            }
+        } else {
+            return Err(InvalidConsumption(Some("Target player is required".to_string())));
+        };
 
-            // Sauvegarder l'ancien niveau de permission
-            let old_lvl = target_player.permission_lvl.load();
-            let new_lvl = executor.permission_lvl.load();
-            target_player.permission_lvl.store(new_lvl);
+        let command = if let Some(Arg::Msg(cmd)) = args.get(ARG_COMMAND) {
+            cmd.clone()
+        } else {
+            return Err(InvalidConsumption(Some("Command is required".to_string())));
+        };
 
-            // Exécuter la commande comme le joueur cible
-            let dispatcher = server.command_dispatcher.read().await;
-            let mut target_sender = CommandSender::Player(target_player.clone());
-            dispatcher.handle_command(&mut target_sender, server, &command).await;
+        let player_name = target_player.gameprofile.name.clone();
 
-            // Restaurer l'ancien niveau de permission
-            target_player.permission_lvl.store(old_lvl);
+        if target_player.has_permission(EXEMPT_PERMISSION).await {
+            log::info!("[SUDO] {} was refused on exempt player {}: {}", sender_name(sender), player_name, command);
+            sender
+                .send_message(TextComponent::text(format!("{} can't be sudoed", player_name)))
+                .await;
+            return Ok(());
+        }
 
-            let player_name = &target_player.gameprofile.name;
-            executor
-                .send_system_message(&TextComponent::text(format!(
-                    "Tried to execute '{}' as {} (see their chat for result)",
-                    command, player_name
+        // Sudoing someone with a higher level would lend the sender that level's rights
+        let elevate = sender.has_permission(ELEVATE_PERMISSION).await;
+        if !may_target(sender_level(sender), target_player.permission_lvl.load(), elevate) {
+            log::info!("[SUDO] {} was refused on higher level player {}: {}", sender_name(sender), player_name, command);
+            sender
+                .send_message(TextComponent::text(format!(
+                    "{} has a higher permission level than you and can't be sudoed",
+                    player_name
                 )))
                 .await;
+            return Ok(());
+        }
 
-            Ok(())
-        } else {
-            Err(InvalidRequirement)
+        // c:<message> makes the target chat instead of running a command
+        if let Some(message) = command.strip_prefix("c:") {
+            log::info!("[SUDO] {} made {} say: {}", sender_name(sender), player_name, message);
+            send_chat_as(server, &target_player, message.trim()).await;
+            sender
+                .send_message(TextComponent::text(format!("Made {} say '{}'", player_name, message.trim())))
+                .await;
+            return Ok(());
         }
+
+        // Commands run with the target's own permissions unless the sender may elevate them
+        // to their own level
+        log::info!(
+            "[SUDO] {} made {} run '{}'{}",
+            sender_name(sender),
+            player_name,
+            command,
+            if elevate { " with elevated permissions" } else { "" }
+        );
+
+        let old_lvl = target_player.permission_lvl.load();
+        if elevate {
+            target_player.permission_lvl.store(sender_level(sender).max(old_lvl));
+        }
+
+        // Run the command as the target player
+        let dispatcher = server.command_dispatcher.read().await;
+        let mut target_sender = CommandSender::Player(target_player.clone());
+        dispatcher.handle_command(&mut target_sender, server, &command).await;
+
+        // Put the target's own permission level back
+        if elevate {
+            target_player.permission_lvl.store(old_lvl);
+        }
+
+        sender
+            .send_message(TextComponent::text(format!(
+                "Tried to execute '{}' as {} (see their chat for result)",
+                command, player_name
+            )))
+            .await;
+
+        Ok(())
+    }
+}
+
+// Permission level of the sender, the console and RCON count as the highest level
+fn sender_level(sender: &CommandSender) -> PermissionLvl {
+    match sender {
+        Player(player) => player.permission_lvl.load(),
+        _ => PermissionLvl::Four,
+    }
+}
+
+// Whether the sender may sudo a target, only up to their own level without sudo.elevate
+fn may_target(sender_lvl: PermissionLvl, target_lvl: PermissionLvl, elevate: bool) -> bool {
+    elevate || target_lvl <= sender_lvl
+}
+
+// Send a chat message as a player, going through the chat event so other plugins
+// and the chat handlers of this plugin see it like a normal message
+pub async fn send_chat_as(server: &Server, player: &Arc<pumpkin::entity::player::Player>, message: &str) {
+    let event = PlayerChatEvent::new(
+        player.clone(),
+        message.to_string(),
+        server.get_all_players().await,
+    );
+    let event = PLUGIN_MANAGER.fire(event).await;
+    if event.cancelled() {
+        return;
+    }
+
+    let chat_line = TextComponent::text(format!("<{}> {}", player.gameprofile.name, event.message));
+    for recipient in &event.recipients {
+        recipient.send_system_message(&chat_line).await;
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
     CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer)
-                .then(argument(ARG_COMMAND, MsgArgConsumer).execute(SudoExecutor)))
+        argument(ARG_TARGET, PlayersOrNickArgumentConsumer)
+            .then(argument(ARG_COMMAND, MsgArgConsumer).execute(SudoExecutor))
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_level_targets_need_elevate() {
+        assert!(!may_target(PermissionLvl::Two, PermissionLvl::Four, false));
+        assert!(may_target(PermissionLvl::Two, PermissionLvl::Four, true));
+    }
+
+    #[test]
+    fn equal_and_lower_level_targets_are_allowed() {
+        assert!(may_target(PermissionLvl::Two, PermissionLvl::Two, false));
+        assert!(may_target(PermissionLvl::Two, PermissionLvl::Zero, false));
+        assert!(may_target(PermissionLvl::Four, PermissionLvl::Four, false));
+    }
+}
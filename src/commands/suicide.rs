@@ -3,15 +3,15 @@ use pumpkin::{
     command::{
         args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::require,
         CommandExecutor, CommandSender,
     },
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
 
+use super::targets::player_required;
+
 const NAMES: [&str; 1] = ["suicide"];
 const DESCRIPTION: &str = "Commit suicide.";
 
@@ -31,14 +31,11 @@ impl CommandExecutor for SuicideExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(SuicideExecutor)
-    )
+    CommandTree::new(NAMES, DESCRIPTION).execute(SuicideExecutor)
 }
\ No newline at end of file
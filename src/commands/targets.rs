@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use pumpkin::command::args::{
+    players::PlayersArgumentConsumer, Arg, ArgumentConsumer, ConsumedArgs, GetClientSideArgParser,
+};
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::tree::RawArgs;
+use pumpkin::command::CommandSender;
+use pumpkin::entity::player::Player;
+use pumpkin::server::Server;
+use pumpkin_protocol::java::client::play::{ArgumentType, CommandSuggestion, SuggestionProviders};
+use pumpkin_util::text::TextComponent;
+use std::sync::Arc;
+
+// Error returned when the console or RCON runs a command that needs a player
+pub fn player_required() -> CommandError {
+    CommandError::CommandFailed(Box::new(TextComponent::text("This command requires a player")))
+}
+
+// Players a command applies to: every selector match, or the sender when no target is given.
+// The console and RCON have to give a target.
+pub fn resolve_targets(
+    sender: &CommandSender,
+    args: &ConsumedArgs,
+    arg_name: &str,
+) -> Result<Vec<Arc<Player>>, CommandError> {
+    if let Some(Arg::Players(players)) = args.get(arg_name) {
+        Ok(players.clone())
+    } else if let CommandSender::Player(player) = sender {
+        Ok(vec![player.clone()])
+    } else {
+        Err(CommandError::CommandFailed(Box::new(TextComponent::text(
+            "This command requires a player target when run from the console",
+        ))))
+    }
+}
+
+// Helper function to check if a target is the sender itself
+pub fn is_self(sender: &CommandSender, player: &Player) -> bool {
+    match sender {
+        CommandSender::Player(sender_player) => sender_player.gameprofile.id == player.gameprofile.id,
+        _ => false,
+    }
+}
+
+// Name shown to other players for whoever ran a command
+pub fn sender_name(sender: &CommandSender) -> String {
+    match sender {
+        CommandSender::Player(player) => player.gameprofile.name.clone(),
+        _ => "Console".to_string(),
+    }
+}
+
+// Short description of who a command applied to, used for the sender's summary message
+pub fn describe_targets(players: &[Arc<Player>]) -> String {
+    match players {
+        [player] => player.gameprofile.name.clone(),
+        _ => format!("{} players", players.len()),
+    }
+}
+
+// Check the <command>.others permission when targeting someone else, and drop
+// players protected by <command>.exempt. Tells the sender and returns None when nobody is left
+pub async fn authorize_targets(
+    sender: &CommandSender,
+    targets: Vec<Arc<Player>>,
+    command: &str,
+) -> Option<Vec<Arc<Player>>> {
+    if targets.iter().all(|player| is_self(sender, player)) {
+        return Some(targets);
+    }
+
+    if !sender.has_permission(&format!("pumpkin-essentials:command.{}.others", command)).await {
+        sender
+            .send_message(TextComponent::text("You don't have permission to use this command on other players"))
+            .await;
+        return None;
+    }
+
+    let exempt_node = format!("pumpkin-essentials:command.{}.exempt", command);
+    let mut allowed = Vec::with_capacity(targets.len());
+    for player in targets {
+        if is_self(sender, &player) || !player.has_permission(&exempt_node).await {
+            allowed.push(player);
+        }
+    }
+
+    if allowed.is_empty() {
+        sender
+            .send_message(TextComponent::text("That player is exempt from this command"))
+            .await;
+        return None;
+    }
+    Some(allowed)
+}
+
+// Player argument that also accepts nicknames, real names and selectors go first
+pub struct PlayersOrNickArgumentConsumer;
+
+impl GetClientSideArgParser for PlayersOrNickArgumentConsumer {
+    fn get_client_side_parser(&self) -> ArgumentType {
+        PlayersArgumentConsumer.get_client_side_parser()
+    }
+
+    fn get_client_side_suggestion_type_override(&self) -> Option<SuggestionProviders> {
+        PlayersArgumentConsumer.get_client_side_suggestion_type_override()
+    }
+}
+
+#[async_trait]
+impl ArgumentConsumer for PlayersOrNickArgumentConsumer {
+    async fn consume<'a>(
+        &'a self,
+        sender: &CommandSender,
+        server: &'a Server,
+        args: &mut RawArgs<'a>,
+    ) -> Option<Arg<'a>> {
+        if let Some(input) = args.last().copied() {
+            if !input.starts_with('@') && server.get_player_by_name(input).await.is_none() {
+                if let Some(player) = super::nick::find_by_nickname(server, input).await {
+                    args.pop();
+                    return Some(Arg::Players(vec![player]));
+                }
+            }
+        }
+        PlayersArgumentConsumer.consume(sender, server, args).await
+    }
+
+    async fn suggest<'a>(
+        &'a self,
+        sender: &CommandSender,
+        server: &'a Server,
+        input: &'a str,
+    ) -> Result<Option<Vec<CommandSuggestion>>, CommandError> {
+        PlayersArgumentConsumer.suggest(sender, server, input).await
+    }
+}
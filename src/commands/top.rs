@@ -3,9 +3,7 @@ use pumpkin::{
     command::{
         args::ConsumedArgs,
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
         tree::CommandTree,
-        tree::builder::require,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -15,6 +13,8 @@ use pumpkin_util::text::TextComponent;
 // TODO: Fix WorldPosition import if needed
 // use pumpkin_util::math::position::WorldPosition;
 
+use super::targets::player_required;
+
 const NAMES: [&str; 1] = ["top"];
 const DESCRIPTION: &str = "Teleport to the highest block at your location.";
 
@@ -79,14 +79,11 @@ impl CommandExecutor for TopExecutor {
             
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(TopExecutor)
-    )
+    CommandTree::new(NAMES, DESCRIPTION).execute(TopExecutor)
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::{Arg, ConsumedArgs},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -17,6 +17,9 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use super::ignore::is_ignoring;
+use super::targets::{player_required, PlayersOrNickArgumentConsumer};
+
 // Global storage for teleport requests
 lazy_static::lazy_static! {
     pub static ref TELEPORT_REQUESTS: Arc<Mutex<HashMap<Uuid, (Uuid, String)>>> =
@@ -57,6 +60,17 @@ impl CommandExecutor for TpaExecutor {
                 return Ok(());
             }
 
+            // Requests from ignored players are never delivered
+            if is_ignoring(target_player.gameprofile.id, target.gameprofile.id).await {
+                target
+                    .send_system_message(&TextComponent::text(format!(
+                        "{} is not accepting teleport requests from you",
+                        target_player.gameprofile.name
+                    )))
+                    .await;
+                return Ok(());
+            }
+
             let mut requests = TELEPORT_REQUESTS.lock().await;
             requests.insert(target_player.gameprofile.id, (target.gameprofile.id, "tpa".to_string()));
 
@@ -76,7 +90,7 @@ impl CommandExecutor for TpaExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
@@ -202,7 +216,7 @@ impl CommandExecutor for TpacceptExecutor {
             
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
@@ -257,29 +271,21 @@ impl CommandExecutor for TpdenyExecutor {
             
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_tpa_command_tree() -> CommandTree {
     CommandTree::new(TPA_NAMES, TPA_DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(TpaExecutor))
+        argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(TpaExecutor)
     )
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_tpaccept_command_tree() -> CommandTree {
-    CommandTree::new(TPACCEPT_NAMES, TPACCEPT_DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(TpacceptExecutor)
-    )
+    CommandTree::new(TPACCEPT_NAMES, TPACCEPT_DESCRIPTION).execute(TpacceptExecutor)
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_tpdeny_command_tree() -> CommandTree {
-    CommandTree::new(TPDENY_NAMES, TPDENY_DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(TpdenyExecutor)
-    )
+    CommandTree::new(TPDENY_NAMES, TPDENY_DESCRIPTION).execute(TpdenyExecutor)
 }
@@ -1,11 +1,11 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::{Arg, ConsumedArgs, players::PlayersArgumentConsumer},
+        args::{Arg, ConsumedArgs},
         dispatcher::CommandError,
-        dispatcher::CommandError::{InvalidConsumption, InvalidRequirement},
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::{argument, require},
+        tree::builder::argument,
         CommandExecutor, CommandSender,
     },
     server::Server,
@@ -13,6 +13,8 @@ use pumpkin::{
 use pumpkin::command::CommandSender::Player;
 use pumpkin_util::text::TextComponent;
 
+use super::ignore::is_ignoring;
+use super::targets::{player_required, PlayersOrNickArgumentConsumer};
 use super::tpa::TELEPORT_REQUESTS;
 
 const NAMES: [&str; 1] = ["tpahere"];
@@ -47,6 +49,17 @@ impl CommandExecutor for TpahereExecutor {
                 return Ok(());
             }
 
+            // Requests from ignored players are never delivered
+            if is_ignoring(target_player.gameprofile.id, target.gameprofile.id).await {
+                target
+                    .send_system_message(&TextComponent::text(format!(
+                        "{} is not accepting teleport requests from you",
+                        target_player.gameprofile.name
+                    )))
+                    .await;
+                return Ok(());
+            }
+
             let mut requests = TELEPORT_REQUESTS.lock().await;
             requests.insert(target_player.gameprofile.id, (target.gameprofile.id, "tpahere".to_string()));
 
@@ -66,15 +79,13 @@ impl CommandExecutor for TpahereExecutor {
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
     CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player())
-            .then(argument(ARG_TARGET, PlayersArgumentConsumer).execute(TpahereExecutor))
+        argument(ARG_TARGET, PlayersOrNickArgumentConsumer).execute(TpahereExecutor)
     )
 }
@@ -1,19 +1,79 @@
 use async_trait::async_trait;
 use pumpkin::{
     command::{
-        args::ConsumedArgs,
+        args::{Arg, ConsumedArgs, message::MsgArgConsumer},
         dispatcher::CommandError,
-        dispatcher::CommandError::InvalidRequirement,
+        dispatcher::CommandError::InvalidConsumption,
         tree::CommandTree,
-        tree::builder::require,
+        tree::builder::{argument, literal},
         CommandExecutor, CommandSender,
     },
+    plugin::{player::player_teleport::PlayerTeleportEvent, EventHandler},
     server::Server,
 };
 use pumpkin::command::CommandSender::Player;
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::text::TextComponent;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::back::BackLocation;
+use super::targets::player_required;
 
 const NAMES: [&str; 1] = ["tpall"];
 const DESCRIPTION: &str = "Teleport all players to you.";
+const ARG_FILTERS: &str = "filters";
+const EXEMPT_PERMISSION: &str = "pumpkin-essentials:command.tpall.exempt";
+
+// Optional filters narrowing down who /tpall pulls, e.g. `/tpall world radius:50 perm:group.vip`
+#[derive(Default, Debug)]
+struct TpallFilters {
+    same_world: bool,
+    radius: Option<f64>,
+    permission: Option<String>,
+}
+
+fn parse_filters(input: &str) -> Result<TpallFilters, String> {
+    let mut filters = TpallFilters::default();
+
+    for token in input.split_whitespace() {
+        if token == "world" {
+            filters.same_world = true;
+        } else if let Some(value) = token.strip_prefix("radius:").or_else(|| token.strip_prefix("r:")) {
+            match value.parse::<f64>() {
+                Ok(radius) if radius.is_finite() && radius > 0.0 => filters.radius = Some(radius),
+                _ => return Err(format!("Invalid radius '{}'", value)),
+            }
+        } else if let Some(node) = token.strip_prefix("perm:") {
+            if node.is_empty() {
+                return Err("Permission filter needs a permission node".to_string());
+            }
+            filters.permission = Some(node.to_string());
+        } else {
+            return Err(format!(
+                "Unknown filter '{}', expected world, radius:<blocks> or perm:<node>",
+                token
+            ));
+        }
+    }
+
+    Ok(filters)
+}
+
+// Where every player was before the most recent /tpall. Players leave the snapshot as
+// soon as they teleport again, so /tpall undo only moves those still at the event
+#[derive(Clone, Debug)]
+pub struct TpallSnapshot {
+    pub destination_world: String,
+    pub players: Vec<(Uuid, BackLocation)>,
+}
+
+// Global storage for the last /tpall, used by /tpall undo
+lazy_static::lazy_static! {
+    pub static ref LAST_TPALL: Arc<Mutex<Option<TpallSnapshot>>> =
+        Arc::new(Mutex::new(None));
+}
 
 struct TpallExecutor;
 
@@ -23,41 +83,197 @@ impl CommandExecutor for TpallExecutor {
         &self,
         sender: &mut CommandSender,
         server: &Server,
-        _args: &ConsumedArgs<'a>,
+        args: &ConsumedArgs<'a>,
     ) -> Result<(), CommandError> {
         if let Player(target) = sender {
+            let filters = if let Some(Arg::Msg(input)) = args.get(ARG_FILTERS) {
+                parse_filters(input).map_err(|err| InvalidConsumption(Some(err)))?
+            } else {
+                TpallFilters::default()
+            };
+
             let target_pos = target.living_entity.entity.pos.load();
             let target_yaw = target.living_entity.entity.yaw.load();
             let target_pitch = target.living_entity.entity.pitch.load();
+            let target_world = crate::world_name(&target.world().await);
+
+            // Validate position before teleporting anyone
+            if !crate::is_valid_destination(target_pos, target_yaw, target_pitch) {
+                log::warn!("[TPALL] Refused teleport to {}: pos={:?}, yaw={}, pitch={}", target.gameprofile.name, target_pos, target_yaw, target_pitch);
+                target
+                    .send_system_message(&TextComponent::text("Your location has invalid coordinates"))
+                    .await;
+                return Ok(());
+            }
 
             let mut teleported_count = 0;
+            let mut skipped_count = 0;
+            let mut origins = Vec::new();
             let players = server.get_all_players().await;
 
             for player in players {
                 if player.gameprofile.id != target.gameprofile.id {
-                    // The teleport will automatically trigger the event handler for back location
-                    player.teleport(target_pos, target_yaw, target_pitch).await;
-                    teleported_count += 1;
+                    let player_pos = player.living_entity.entity.pos.load();
+                    let player_world = crate::world_name(&player.world().await);
+
+                    if filters.same_world && player_world != target_world {
+                        continue;
+                    }
+                    if let Some(radius) = filters.radius {
+                        let dx = player_pos.x - target_pos.x;
+                        let dy = player_pos.y - target_pos.y;
+                        let dz = player_pos.z - target_pos.z;
+                        if player_world != target_world || dx * dx + dy * dy + dz * dz > radius * radius {
+                            continue;
+                        }
+                    }
+                    if let Some(node) = &filters.permission {
+                        if !player.has_permission(node).await {
+                            continue;
+                        }
+                    }
+                    if player.has_permission(EXEMPT_PERMISSION).await {
+                        continue;
+                    }
+
+                    // Check teleport cooldown
+                    if !crate::can_teleport(player.gameprofile.id).await {
+                        skipped_count += 1;
+                        continue;
+                    }
+
+                    origins.push((
+                        player.gameprofile.id,
+                        BackLocation {
+                            position: player_pos,
+                            yaw: player.living_entity.entity.yaw.load(),
+                            pitch: player.living_entity.entity.pitch.load(),
+                            world_name: player_world,
+                        },
+                    ));
+
+                    // Players in another dimension are moved to the sender's world as well
+                    if crate::teleport_to_world(
+                        server,
+                        &player,
+                        &target_world,
+                        target_pos,
+                        target_yaw,
+                        target_pitch,
+                    ).await {
+                        teleported_count += 1;
+                    } else {
+                        origins.pop();
+                        log::warn!("[TPALL] Could not find world '{}' for {}", target_world, player.gameprofile.name);
+                    }
                 }
             }
 
+            // Stored after the teleports so they don't remove the players from the snapshot
+            *LAST_TPALL.lock().await = Some(TpallSnapshot {
+                destination_world: target_world,
+                players: origins,
+            });
+
+            let message = if skipped_count > 0 {
+                format!(
+                    "Teleported {} players to you ({} on teleport cooldown)",
+                    teleported_count,
+                    skipped_count
+                )
+            } else {
+                format!("Teleported {} players to you", teleported_count)
+            };
             target
-                .send_system_message(&TextComponent::text(format!(
-                    "Teleported {} players to you",
-                    teleported_count
-                )))
+                .send_system_message(&TextComponent::text(message))
                 .await;
 
             Ok(())
         } else {
-            Err(InvalidRequirement)
+            Err(player_required())
+        }
+    }
+}
+
+struct TpallUndoExecutor;
+
+#[async_trait]
+impl CommandExecutor for TpallUndoExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender,
+        server: &Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let Some(snapshot) = LAST_TPALL.lock().await.take() else {
+            sender
+                .send_message(TextComponent::text("There is no /tpall to undo"))
+                .await;
+            return Ok(());
+        };
+
+        let mut restored_count = 0;
+        let mut skipped_count = 0;
+
+        for (player_uuid, origin) in snapshot.players {
+            // Players who logged out since the /tpall are skipped
+            let Some(player) = server.get_player_by_uuid(player_uuid).await else {
+                skipped_count += 1;
+                continue;
+            };
+
+            // Players who teleported since were already removed from the snapshot, this also
+            // leaves alone those who went through a portal
+            if crate::world_name(&player.world().await) != snapshot.destination_world {
+                skipped_count += 1;
+                continue;
+            }
+
+            if crate::teleport_to_world(
+                server,
+                &player,
+                &origin.world_name,
+                origin.position,
+                origin.yaw,
+                origin.pitch,
+            ).await {
+                restored_count += 1;
+            } else {
+                log::warn!("[TPALL] Could not find world '{}' to restore {}", origin.world_name, player.gameprofile.name);
+                skipped_count += 1;
+            }
+        }
+
+        sender
+            .send_message(TextComponent::text(format!(
+                "Restored {} players to their previous location ({} skipped)",
+                restored_count,
+                skipped_count
+            )))
+            .await;
+
+        Ok(())
+    }
+}
+
+// Event handler for PlayerTeleportEvent removing the player from the last /tpall, their
+// next teleport means they left the event
+pub struct TpallTeleportHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerTeleportEvent> for TpallTeleportHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerTeleportEvent) {
+        if let Some(snapshot) = LAST_TPALL.lock().await.as_mut() {
+            let player_uuid = event.player.gameprofile.id;
+            snapshot.players.retain(|(uuid, _)| *uuid != player_uuid);
         }
     }
 }
 
-#[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree() -> CommandTree {
-    CommandTree::new(NAMES, DESCRIPTION).then(
-        require(|sender| sender.is_player()).execute(TpallExecutor)
-    )
-} use pumpkin_util::text::TextComponent;
+    CommandTree::new(NAMES, DESCRIPTION)
+        .execute(TpallExecutor)
+        .then(literal("undo").execute(TpallUndoExecutor))
+        .then(argument(ARG_FILTERS, MsgArgConsumer).execute(TpallExecutor))
+}
@@ -0,0 +1,174 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::chat_filter::FilterRule;
+use crate::chat_format::ChatGroup;
+use crate::storage;
+
+const CONFIG_FILE: &str = "config.yml";
+
+// Plugin configuration, read from config.yml in the plugin data folder
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EssentialsConfig {
+    // Keep fly, god and speed states in player_states.yml so they survive a restart
+    pub persist_player_states: bool,
+    pub speed: SpeedConfig,
+    pub heal: HealConfig,
+    pub repair: RepairConfig,
+    pub mail: MailConfig,
+    pub chat_filter: ChatFilterConfig,
+    pub mute: MuteConfig,
+    pub chat: ChatConfig,
+}
+
+impl Default for EssentialsConfig {
+    fn default() -> Self {
+        EssentialsConfig {
+            persist_player_states: true,
+            speed: SpeedConfig::default(),
+            heal: HealConfig::default(),
+            repair: RepairConfig::default(),
+            mail: MailConfig::default(),
+            chat_filter: ChatFilterConfig::default(),
+            mute: MuteConfig::default(),
+            chat: ChatConfig::default(),
+        }
+    }
+}
+
+// /speed settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedConfig {
+    // Highest /speed value (0-10) for players without a speed.max.<n> permission. The
+    // permissions only raise the cap, so this has to stay below the ranks using them
+    pub default_max: f32,
+}
+
+impl Default for SpeedConfig {
+    fn default() -> Self {
+        SpeedConfig { default_max: 2.0 }
+    }
+}
+
+// /heal settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealConfig {
+    pub restore_food: bool,
+    pub extinguish_fire: bool,
+    pub clear_negative_effects: bool,
+    // Seconds between two /heal uses, 0 disables the cooldown
+    pub cooldown_seconds: u64,
+}
+
+impl Default for HealConfig {
+    fn default() -> Self {
+        HealConfig {
+            restore_food: true,
+            extinguish_fire: true,
+            clear_negative_effects: true,
+            cooldown_seconds: 60,
+        }
+    }
+}
+
+// /repair settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepairConfig {
+    // Item ids that can never be repaired, e.g. minecraft:elytra
+    pub blacklist: Vec<String>,
+}
+
+// /mail settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MailConfig {
+    // Seconds a player has to wait between two mails, bypassed with mail.bypasscooldown
+    pub send_cooldown_seconds: u64,
+    // Mails kept per player before new ones are refused
+    pub max_mailbox_size: usize,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        MailConfig {
+            send_cooldown_seconds: 30,
+            max_mailbox_size: 50,
+        }
+    }
+}
+
+// Chat filter settings, rules apply to chat, private messages and signs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatFilterConfig {
+    pub enabled: bool,
+    // Banned words or regexes, each with censor, block or block_and_warn as action
+    pub rules: Vec<FilterRule>,
+}
+
+impl Default for ChatFilterConfig {
+    fn default() -> Self {
+        ChatFilterConfig {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+// /mute settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MuteConfig {
+    // Commands muted players can't use besides chat and private messages, e.g. "me" or "mail send"
+    pub blocked_commands: Vec<String>,
+}
+
+impl Default for MuteConfig {
+    fn default() -> Self {
+        MuteConfig {
+            blocked_commands: vec!["me".to_string(), "say".to_string(), "mail send".to_string()],
+        }
+    }
+}
+
+// Chat format settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    // Use the format below instead of the vanilla chat format
+    pub enabled: bool,
+    // Placeholders: {prefix}, {suffix}, {group}, {displayname}, {name}, {world} and {message}
+    pub format: String,
+    // Chat groups, the first one a player has the permission for is used
+    pub groups: Vec<ChatGroup>,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        ChatConfig {
+            enabled: true,
+            format: "{prefix}{displayname}&r{suffix}: {message}".to_string(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+static CONFIG: OnceCell<EssentialsConfig> = OnceCell::new();
+
+// Load config.yml, writing the defaults when it doesn't exist yet
+pub fn load() {
+    let config: EssentialsConfig = storage::load_yaml(CONFIG_FILE);
+    if !storage::exists(CONFIG_FILE) {
+        storage::save_yaml(CONFIG_FILE, &config);
+    }
+    let _ = CONFIG.set(config);
+}
+
+// Current configuration (defaults if config.yml was not loaded yet)
+pub fn get() -> &'static EssentialsConfig {
+    CONFIG.get_or_init(EssentialsConfig::default)
+}
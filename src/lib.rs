@@ -2,22 +2,39 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
 use pumpkin::{
+    entity::player::Player,
     plugin::{
-        player::player_teleport::PlayerTeleportEvent,
+        block::sign_change::SignChangeEvent,
+        player::{
+            player_chat::PlayerChatEvent, player_command_send::PlayerCommandSendEvent,
+            player_damage::PlayerDamageEvent,
+            player_join::PlayerJoinEvent, player_respawn::PlayerRespawnEvent,
+            player_teleport::PlayerTeleportEvent,
+        },
         Context, EventPriority
     },
+    server::Server,
+    world::World,
 };
 use pumpkin_api_macros::{plugin_impl, plugin_method};
 use pumpkin_util::{
+    math::vector3::Vector3,
     PermissionLvl,
     permission::{Permission, PermissionDefault},
 };
 use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
+mod chat_filter;
+mod chat_format;
 mod commands;
+mod config;
+mod player_state;
+mod storage;
+mod text_format;
 
 const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -25,9 +42,9 @@ const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 static TELEPORT_COOLDOWNS: Lazy<Arc<Mutex<HashMap<Uuid, Instant>>>> = 
     Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
 
-// Fly state tracking system
-static FLY_STATES: Lazy<Arc<Mutex<HashMap<Uuid, bool>>>> = 
-    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+// Tasks started on load, aborted on unload
+static BACKGROUND_TASKS: Lazy<Mutex<Vec<JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
 
 const TELEPORT_COOLDOWN_DURATION: Duration = Duration::from_millis(500); // 500ms cooldown
 
@@ -46,16 +63,67 @@ async fn can_teleport(player_uuid: Uuid) -> bool {
     true
 }
 
-// Helper function to get fly state for a player
-pub async fn get_fly_state(player_uuid: Uuid) -> bool {
-    let fly_states = FLY_STATES.lock().await;
-    fly_states.get(&player_uuid).copied().unwrap_or(false)
+// Helper function to check that a destination is safe to teleport to
+pub fn is_valid_destination(position: Vector3<f64>, yaw: f32, pitch: f32) -> bool {
+    World::is_valid(position)
+        && position.x.is_finite() && position.y.is_finite() && position.z.is_finite()
+        && yaw.is_finite() && pitch.is_finite()
+}
+
+// Helper function to get the namespaced name of a world, e.g. minecraft:the_nether, used to
+// remember where players were and to pick a world in commands
+pub fn world_name(world: &World) -> String {
+    // The dimension type prints as its variant name, TheNether becomes the_nether
+    let mut name = String::from("minecraft:");
+    for (index, c) in format!("{:?}", world.dimension_type).chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+// Helper function to find a world by its namespaced name, the name without minecraft: also works
+pub async fn find_world(server: &Server, name: &str) -> Option<Arc<World>> {
+    let name = name.to_lowercase();
+    let name = if name.contains(':') { name } else { format!("minecraft:{}", name) };
+    server
+        .worlds
+        .read()
+        .await
+        .iter()
+        .find(|world| world_name(world) == name)
+        .cloned()
 }
 
-// Helper function to set fly state for a player
-pub async fn set_fly_state(player_uuid: Uuid, state: bool) {
-    let mut fly_states = FLY_STATES.lock().await;
-    fly_states.insert(player_uuid, state);
+// Helper function to list the names of every loaded world
+pub async fn world_names(server: &Server) -> Vec<String> {
+    server.worlds.read().await.iter().map(|world| world_name(world)).collect()
+}
+
+// Helper function to teleport a player to a position, switching worlds when needed
+pub async fn teleport_to_world(
+    server: &Server,
+    player: &Arc<Player>,
+    target_world: &str,
+    position: Vector3<f64>,
+    yaw: f32,
+    pitch: f32,
+) -> bool {
+    let current_world = player.world().await;
+    if world_name(&current_world) == target_world {
+        player.teleport(position, yaw, pitch).await;
+        return true;
+    }
+
+    match find_world(server, target_world).await {
+        Some(world) => {
+            player.clone().teleport_world(world, position, Some(yaw), Some(pitch)).await;
+            true
+        }
+        None => false,
+    }
 }
 
 async fn register_commands(context: &Context) -> Result<(), String> {
@@ -66,10 +134,7 @@ async fn register_commands(context: &Context) -> Result<(), String> {
         ("delhome", "Delete a home location"),
         ("renamehome", "Rename a home location"),
         ("back", "Teleport to your previous location"),
-        ("gmc", "Change to creative mode"),
-        ("gms", "Change to survival mode"),
-        ("gma", "Change to adventure mode"),
-        ("gmsp", "Change to spectator mode"),
+        ("gamemode", "Use /gm and the gamemode shortcuts"),
         ("top", "Teleport to the highest block"),
         ("tpa", "Request to teleport to another player"),
         ("tpaccept", "Accept a teleport request"),
@@ -85,7 +150,15 @@ async fn register_commands(context: &Context) -> Result<(), String> {
         ("suicide", "Commit suicide"),
         ("killall", "Kill all entities"),
         ("enderchest", "Open an enderchest"),
+        ("invsee", "View another player's inventory"),
         ("ignore", "Ignore a player"),
+        ("msg", "Send a private message"),
+        ("reply", "Reply to a private message"),
+        ("socialspy", "See other players' private messages"),
+        ("mail", "Send and read mail"),
+        ("mute", "Mute a player"),
+        ("unmute", "Unmute a player"),
+        ("nick", "Set a nickname"),
         ("kickall", "Kick all players"),
         ("sudo", "Execute a command as another player"),
         ("speed", "Set walk or fly speed"),
@@ -100,6 +173,101 @@ async fn register_commands(context: &Context) -> Result<(), String> {
         context.register_permission(permission).await?;
     }
 
+    // Extra permissions that are not tied to running a command
+    let extra_permissions = [
+        ("tpall.exempt", "Never be pulled by /tpall", PermissionDefault::Deny),
+        ("kickall.exempt", "Never be kicked by /kickall", PermissionDefault::Deny),
+        ("mail.sendall", "Send mail to every player", PermissionDefault::Op(PermissionLvl::Two)),
+        ("mail.bypasscooldown", "Send mail without waiting for the cooldown", PermissionDefault::Op(PermissionLvl::Two)),
+        ("ignore.exempt", "Cannot be ignored with /ignore", PermissionDefault::Op(PermissionLvl::Two)),
+        ("sudo.exempt", "Cannot be targeted by /sudo", PermissionDefault::Deny),
+        ("sudo.elevate", "Run /sudo commands with your own permission level", PermissionDefault::Op(PermissionLvl::Four)),
+        ("god.list", "List players with god mode enabled", PermissionDefault::Op(PermissionLvl::Two)),
+        ("heal.bypasscooldown", "Use /heal without waiting for the cooldown", PermissionDefault::Op(PermissionLvl::Two)),
+        ("gamemode.notify", "Be notified of gamemode changes", PermissionDefault::Op(PermissionLvl::Two)),
+        ("enderchest.modify", "Edit other players' ender chests", PermissionDefault::Op(PermissionLvl::Two)),
+        ("invsee.modify", "Edit inventories opened with /invsee", PermissionDefault::Op(PermissionLvl::Two)),
+        ("invsee.exempt", "Inventory can't be opened with /invsee", PermissionDefault::Deny),
+        ("nick.color", "Use color codes in nicknames", PermissionDefault::Op(PermissionLvl::Two)),
+        ("nick.format", "Use format codes such as bold in nicknames", PermissionDefault::Op(PermissionLvl::Two)),
+        ("repair.all", "Repair every item in your inventory", PermissionDefault::Op(PermissionLvl::Two)),
+        ("repair.enchanted", "Repair enchanted items", PermissionDefault::Op(PermissionLvl::Two)),
+        ("chatfilter.bypass", "Chat without going through the chat filter", PermissionDefault::Op(PermissionLvl::Three)),
+        ("chatfilter.notify", "Be warned about messages blocked by the chat filter", PermissionDefault::Op(PermissionLvl::Two)),
+        ("chat.color", "Use color codes in chat messages", PermissionDefault::Op(PermissionLvl::Two)),
+    ];
+
+    for (node, description, default) in extra_permissions.into_iter() {
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.{}", node),
+            description,
+            default,
+        );
+        context.register_permission(permission).await?;
+    }
+
+    // One permission per configured chat group
+    for group in &config::get().chat.groups {
+        let permission = Permission::new(
+            &chat_format::group_permission(group),
+            &format!("Use the {} chat prefix", group.name),
+            PermissionDefault::Deny,
+        );
+        context.register_permission(permission).await?;
+    }
+
+    // Using a command on other players needs <command>.others, <command>.exempt protects from it
+    for cmd in ["heal", "feed", "fly", "god", "speed", "ping", "enderchest", "nick"] {
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.{}.others", cmd),
+            &format!("Use /{} on other players", cmd),
+            PermissionDefault::Op(PermissionLvl::Two),
+        );
+        context.register_permission(permission).await?;
+
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.{}.exempt", cmd),
+            &format!("Cannot be targeted by /{} from other players", cmd),
+            PermissionDefault::Deny,
+        );
+        context.register_permission(permission).await?;
+    }
+
+    // Per mode gamemode permissions, with separate nodes for changing other players
+    for gamemode in commands::gamemode::GAMEMODES {
+        let mode_name = commands::gamemode::gamemode_name(gamemode);
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.gamemode.{}", mode_name),
+            &format!("Switch to {} mode", mode_name),
+            PermissionDefault::Op(PermissionLvl::One), // Same level as the old command.gmc-style nodes
+        );
+        context.register_permission(permission).await?;
+
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.gamemode.{}.others", mode_name),
+            &format!("Switch other players to {} mode", mode_name),
+            PermissionDefault::Op(PermissionLvl::Two),
+        );
+        context.register_permission(permission).await?;
+
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.gamemode.{}.exempt", mode_name),
+            &format!("Cannot be switched to {} mode by other players", mode_name),
+            PermissionDefault::Deny,
+        );
+        context.register_permission(permission).await?;
+    }
+
+    // Speed caps, /speed can go up to the highest speed.max.<n> a player holds
+    for level in 1..=10 {
+        let permission = Permission::new(
+            &format!("{PLUGIN_NAME}:command.speed.max.{}", level),
+            &format!("Allow /speed values up to {}", level),
+            PermissionDefault::Deny,
+        );
+        context.register_permission(permission).await?;
+    }
+
     // Register all our commands
     context.register_command(
         commands::home::init_command_tree(),
@@ -127,23 +295,28 @@ async fn register_commands(context: &Context) -> Result<(), String> {
     ).await;
     
     context.register_command(
-        commands::gmc::init_command_tree(),
-        &format!("{PLUGIN_NAME}:command.gmc")
+        commands::gamemode::init_gm_command_tree(),
+        &format!("{PLUGIN_NAME}:command.gamemode")
+    ).await;
+    
+    context.register_command(
+        commands::gamemode::init_gmc_command_tree(),
+        &format!("{PLUGIN_NAME}:command.gamemode")
     ).await;
     
     context.register_command(
-        commands::gms::init_command_tree(),
-        &format!("{PLUGIN_NAME}:command.gms")
+        commands::gamemode::init_gms_command_tree(),
+        &format!("{PLUGIN_NAME}:command.gamemode")
     ).await;
     
     context.register_command(
-        commands::gma::init_command_tree(),
-        &format!("{PLUGIN_NAME}:command.gma")
+        commands::gamemode::init_gma_command_tree(),
+        &format!("{PLUGIN_NAME}:command.gamemode")
     ).await;
     
     context.register_command(
-        commands::gmsp::init_command_tree(),
-        &format!("{PLUGIN_NAME}:command.gmsp")
+        commands::gamemode::init_gmsp_command_tree(),
+        &format!("{PLUGIN_NAME}:command.gamemode")
     ).await;
     
     context.register_command(
@@ -221,11 +394,51 @@ async fn register_commands(context: &Context) -> Result<(), String> {
         &format!("{PLUGIN_NAME}:command.enderchest")
     ).await;
 
+    context.register_command(
+        commands::invsee::init_command_tree(),
+        &format!("{PLUGIN_NAME}:command.invsee")
+    ).await;
+
     context.register_command(
         commands::ignore::init_command_tree(),
         &format!("{PLUGIN_NAME}:command.ignore")
     ).await;
 
+    context.register_command(
+        commands::msg::init_msg_command_tree(),
+        &format!("{PLUGIN_NAME}:command.msg")
+    ).await;
+
+    context.register_command(
+        commands::msg::init_reply_command_tree(),
+        &format!("{PLUGIN_NAME}:command.reply")
+    ).await;
+
+    context.register_command(
+        commands::msg::init_socialspy_command_tree(),
+        &format!("{PLUGIN_NAME}:command.socialspy")
+    ).await;
+
+    context.register_command(
+        commands::mail::init_command_tree(),
+        &format!("{PLUGIN_NAME}:command.mail")
+    ).await;
+
+    context.register_command(
+        commands::mute::init_mute_command_tree(),
+        &format!("{PLUGIN_NAME}:command.mute")
+    ).await;
+
+    context.register_command(
+        commands::mute::init_unmute_command_tree(),
+        &format!("{PLUGIN_NAME}:command.unmute")
+    ).await;
+
+    context.register_command(
+        commands::nick::init_command_tree(),
+        &format!("{PLUGIN_NAME}:command.nick")
+    ).await;
+
     context.register_command(
         commands::kickall::init_command_tree(),
         &format!("{PLUGIN_NAME}:command.kickall")
@@ -249,7 +462,88 @@ async fn register_events(context: &Context) {
     context.register_event::<PlayerTeleportEvent, commands::back::BackLocationHandler>(
         Arc::new(commands::back::BackLocationHandler),
         EventPriority::Normal,
-        false, // Non-blocking handler
+        true, // handle_blocking only runs for blocking handlers
+    ).await;
+
+    // Forget the /tpall origin of players who teleport away from the event
+    context.register_event::<PlayerTeleportEvent, commands::tpall::TpallTeleportHandler>(
+        Arc::new(commands::tpall::TpallTeleportHandler),
+        EventPriority::Normal,
+        true,
+    ).await;
+
+    // Remember player names and restore fly, god and speed when players join or respawn
+    context.register_event::<PlayerJoinEvent, player_state::PlayerStateJoinHandler>(
+        Arc::new(player_state::PlayerStateJoinHandler),
+        EventPriority::Normal,
+        true,
+    ).await;
+
+    // Announce unread mail
+    context.register_event::<PlayerJoinEvent, commands::mail::MailJoinHandler>(
+        Arc::new(commands::mail::MailJoinHandler),
+        EventPriority::Normal,
+        true, // handle_blocking only runs for blocking handlers
+    ).await;
+
+    // Show saved nicknames when players join
+    context.register_event::<PlayerJoinEvent, commands::nick::NickJoinHandler>(
+        Arc::new(commands::nick::NickJoinHandler),
+        EventPriority::Normal,
+        true, // handle_blocking only runs for blocking handlers
+    ).await;
+
+    context.register_event::<PlayerRespawnEvent, player_state::PlayerStateRespawnHandler>(
+        Arc::new(player_state::PlayerStateRespawnHandler),
+        EventPriority::Normal,
+        true,
+    ).await;
+
+    // Cancel all damage for players in god mode
+    context.register_event::<PlayerDamageEvent, commands::god::GodDamageHandler>(
+        Arc::new(commands::god::GodDamageHandler),
+        EventPriority::Highest,
+        true, // Blocking so the damage can be cancelled
+    ).await;
+
+    // Block chat and the configured commands from muted players
+    context.register_event::<PlayerChatEvent, commands::mute::MuteChatHandler>(
+        Arc::new(commands::mute::MuteChatHandler),
+        EventPriority::Highest,
+        true,
+    ).await;
+
+    context.register_event::<PlayerCommandSendEvent, commands::mute::MuteCommandHandler>(
+        Arc::new(commands::mute::MuteCommandHandler),
+        EventPriority::Highest,
+        true,
+    ).await;
+
+    // Censor or block filtered chat messages and sign text
+    context.register_event::<PlayerChatEvent, chat_filter::ChatFilterHandler>(
+        Arc::new(chat_filter::ChatFilterHandler),
+        EventPriority::High,
+        true,
+    ).await;
+
+    context.register_event::<SignChangeEvent, chat_filter::SignFilterHandler>(
+        Arc::new(chat_filter::SignFilterHandler),
+        EventPriority::High,
+        true,
+    ).await;
+
+    // Hide chat from players ignoring the sender when the chat format is off
+    context.register_event::<PlayerChatEvent, commands::ignore::IgnoreChatHandler>(
+        Arc::new(commands::ignore::IgnoreChatHandler),
+        EventPriority::Normal,
+        true, // Blocking so the vanilla message can be cancelled
+    ).await;
+
+    // Send chat with the configured format once the other chat handlers are done
+    context.register_event::<PlayerChatEvent, chat_format::ChatFormatHandler>(
+        Arc::new(chat_format::ChatFormatHandler),
+        EventPriority::Lowest,
+        true,
     ).await;
 }
 
@@ -257,13 +551,36 @@ async fn register_events(context: &Context) {
 async fn on_load(&mut self, context: &Context) -> Result<(), String> {
     pumpkin::init_log!();
 
+    storage::init(&context.get_data_folder());
+    config::load();
+    player_state::load().await;
+    commands::ignore::load().await;
+    commands::mail::load().await;
+    commands::mute::load().await;
+    commands::nick::load().await;
+
     register_commands(context).await?;
     register_events(context).await;
+    BACKGROUND_TASKS.lock().await.extend([
+        commands::god::start_hunger_task(context.server.clone()),
+        commands::ping::start_latency_task(context.server.clone()),
+    ]);
 
     log::info!("Extended Commands Plugin has been loaded.");
     Ok(())
 }
 
+#[plugin_method]
+async fn on_unload(&mut self, _context: &Context) -> Result<(), String> {
+    // Stop the background loops so they don't outlive the plugin
+    for task in BACKGROUND_TASKS.lock().await.drain(..) {
+        task.abort();
+    }
+
+    log::info!("Extended Commands Plugin has been unloaded.");
+    Ok(())
+}
+
 #[plugin_impl]
 pub struct Plugin {}
 
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use pumpkin::{
+    entity::player::Player,
+    plugin::{
+        player::{player_join::PlayerJoinEvent, player_respawn::PlayerRespawnEvent},
+        EventHandler,
+    },
+    server::Server,
+};
+use pumpkin_api_macros::with_runtime;
+use pumpkin_util::GameMode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{config, storage};
+
+const STATES_FILE: &str = "player_states.yml";
+const KNOWN_PLAYERS_FILE: &str = "known_players.yml";
+
+// Abilities set through /fly, /god and /speed that should survive relogs and respawns
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerState {
+    pub fly: bool,
+    pub god: bool,
+    pub walk_speed: Option<f32>,
+    pub fly_speed: Option<f32>,
+}
+
+impl PlayerState {
+    fn is_default(&self) -> bool {
+        !self.fly && !self.god && self.walk_speed.is_none() && self.fly_speed.is_none()
+    }
+}
+
+// Player state tracking system
+static PLAYER_STATES: Lazy<Arc<Mutex<HashMap<Uuid, PlayerState>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Last known name of every player who joined, so commands can find offline players
+static KNOWN_PLAYERS: Lazy<Arc<Mutex<HashMap<Uuid, String>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Load known players, and saved states when persistence is enabled, from disk
+pub async fn load() {
+    let known_players: HashMap<Uuid, String> = storage::load_yaml(KNOWN_PLAYERS_FILE);
+    *KNOWN_PLAYERS.lock().await = known_players;
+
+    if !config::get().persist_player_states {
+        return;
+    }
+    let states: HashMap<Uuid, PlayerState> = storage::load_yaml(STATES_FILE);
+    *PLAYER_STATES.lock().await = states;
+}
+
+// Helper function to save a player's current name to the known players
+pub async fn remember_player(player: &Player) {
+    let mut known_players = KNOWN_PLAYERS.lock().await;
+    if known_players.get(&player.gameprofile.id) != Some(&player.gameprofile.name) {
+        known_players.insert(player.gameprofile.id, player.gameprofile.name.clone());
+        storage::save_yaml(KNOWN_PLAYERS_FILE, &*known_players);
+    }
+}
+
+// Helper function to find a player's UUID from their name, online or not
+pub async fn find_player_uuid(server: &Server, name: &str) -> Option<(Uuid, String)> {
+    if let Some(player) = server.get_player_by_name(name).await {
+        return Some((player.gameprofile.id, player.gameprofile.name.clone()));
+    }
+    let known_players = KNOWN_PLAYERS.lock().await;
+    known_players
+        .iter()
+        .find(|(_, known_name)| known_name.eq_ignore_ascii_case(name))
+        .map(|(uuid, known_name)| (*uuid, known_name.clone()))
+}
+
+// Helper function to get the UUID of every player who ever joined
+pub async fn known_player_uuids() -> Vec<Uuid> {
+    KNOWN_PLAYERS.lock().await.keys().copied().collect()
+}
+
+// Helper function to change a player's state and save it when persistence is enabled
+async fn update_state(player_uuid: Uuid, update: impl FnOnce(&mut PlayerState)) {
+    let mut states = PLAYER_STATES.lock().await;
+    let state = states.entry(player_uuid).or_default();
+    update(state);
+    if state.is_default() {
+        states.remove(&player_uuid);
+    }
+
+    if config::get().persist_player_states {
+        storage::save_yaml(STATES_FILE, &*states);
+    }
+}
+
+// Helper function to get the saved state for a player
+pub async fn get_state(player_uuid: Uuid) -> PlayerState {
+    let states = PLAYER_STATES.lock().await;
+    states.get(&player_uuid).cloned().unwrap_or_default()
+}
+
+// Helper function to get fly state for a player
+pub async fn get_fly_state(player_uuid: Uuid) -> bool {
+    get_state(player_uuid).await.fly
+}
+
+// Helper function to set fly state for a player
+pub async fn set_fly_state(player_uuid: Uuid, state: bool) {
+    update_state(player_uuid, |player_state| player_state.fly = state).await;
+}
+
+// Helper function to set god state for a player
+pub async fn set_god_state(player_uuid: Uuid, state: bool) {
+    update_state(player_uuid, |player_state| player_state.god = state).await;
+}
+
+// Helper function to remember the walk speed given by /speed
+pub async fn set_walk_speed(player_uuid: Uuid, speed: Option<f32>) {
+    update_state(player_uuid, |player_state| player_state.walk_speed = speed).await;
+}
+
+// Helper function to remember the fly speed given by /speed
+pub async fn set_fly_speed(player_uuid: Uuid, speed: Option<f32>) {
+    update_state(player_uuid, |player_state| player_state.fly_speed = speed).await;
+}
+
+// Reapply the saved state, dropping anything the player is no longer allowed to have
+pub async fn apply_state(player: &Arc<Player>) {
+    let player_uuid = player.gameprofile.id;
+    let state = get_state(player_uuid).await;
+    if state.is_default() {
+        return;
+    }
+
+    let can_fly = player.has_permission("pumpkin-essentials:command.fly").await;
+    let can_god = player.has_permission("pumpkin-essentials:command.god").await;
+    let can_speed = player.has_permission("pumpkin-essentials:command.speed").await;
+
+    {
+        let mut abilities = player.abilities.lock().await;
+        if state.fly && can_fly {
+            abilities.allow_flying = true;
+        }
+        if state.god && can_god {
+            abilities.invulnerable = true;
+        }
+        if can_speed {
+            if let Some(walk_speed) = state.walk_speed {
+                abilities.walk_speed = walk_speed;
+            }
+            if let Some(fly_speed) = state.fly_speed {
+                abilities.fly_speed = fly_speed;
+            }
+        }
+    }
+    player.send_abilities_update().await;
+
+    update_state(player_uuid, |player_state| {
+        player_state.fly &= can_fly;
+        player_state.god &= can_god;
+        if !can_speed {
+            player_state.walk_speed = None;
+            player_state.fly_speed = None;
+        }
+    }).await;
+}
+
+// Helper function telling whether flight comes from the gamemode rather than /fly
+pub fn gamemode_grants_flight(gamemode: GameMode) -> bool {
+    matches!(gamemode, GameMode::Creative | GameMode::Spectator)
+}
+
+// Helper function telling whether the gamemode already makes the player invulnerable
+pub fn gamemode_grants_invulnerability(gamemode: GameMode) -> bool {
+    matches!(gamemode, GameMode::Creative | GameMode::Spectator)
+}
+
+// Set invulnerability from the gamemode and /god together, so turning god mode off
+// doesn't take it away from a player in creative or spectator
+pub async fn resync_god(player: &Arc<Player>) {
+    let invulnerable = gamemode_grants_invulnerability(player.gamemode.load())
+        || get_state(player.gameprofile.id).await.god;
+    player.abilities.lock().await.invulnerable = invulnerable;
+    player.send_abilities_update().await;
+}
+
+// Put /fly flight back after a gamemode change reset the abilities. Called by the
+// plugin's gamemode commands once the change is applied, the vanilla /gamemode is not covered
+pub async fn resync_fly(player: &Arc<Player>, was_flying: bool) {
+    if gamemode_grants_flight(player.gamemode.load()) {
+        return;
+    }
+
+    let fly_enabled = get_fly_state(player.gameprofile.id).await
+        && player.has_permission("pumpkin-essentials:command.fly").await;
+    {
+        let mut abilities = player.abilities.lock().await;
+        abilities.allow_flying = fly_enabled;
+        abilities.flying = fly_enabled && was_flying;
+    }
+    player.send_abilities_update().await;
+}
+
+// Event handler for PlayerJoinEvent to remember the player's name and restore fly, god and speed
+pub struct PlayerStateJoinHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerJoinEvent> for PlayerStateJoinHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerJoinEvent) {
+        remember_player(&event.player).await;
+        apply_state(&event.player).await;
+    }
+}
+
+// Event handler for PlayerRespawnEvent, respawning resets abilities
+pub struct PlayerStateRespawnHandler;
+
+#[with_runtime(global)]
+#[async_trait]
+impl EventHandler<PlayerRespawnEvent> for PlayerStateRespawnHandler {
+    async fn handle_blocking(&self, _server: &Arc<Server>, event: &mut PlayerRespawnEvent) {
+        apply_state(&event.player).await;
+    }
+}
@@ -0,0 +1,72 @@
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Plugin data folder, set once when the plugin loads
+static DATA_FOLDER: OnceCell<PathBuf> = OnceCell::new();
+
+// Files that exist but could not be parsed, never overwritten so the admin can fix them
+static UNREADABLE_FILES: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+// Remember the plugin data folder and make sure it exists
+pub fn init(data_folder: &str) {
+    let path = PathBuf::from(data_folder);
+    if let Err(err) = fs::create_dir_all(&path) {
+        log::error!("Could not create data folder {}: {}", path.display(), err);
+    }
+    let _ = DATA_FOLDER.set(path);
+}
+
+// Full path of a file inside the plugin data folder
+pub fn data_path(file: &str) -> PathBuf {
+    DATA_FOLDER
+        .get()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("plugins").join(crate::PLUGIN_NAME))
+        .join(file)
+}
+
+// Check if a file exists in the data folder
+pub fn exists(file: &str) -> bool {
+    data_path(file).exists()
+}
+
+// Load a yml file from the data folder, falling back to the default value when missing or invalid.
+// An invalid file is left untouched: later saves to it are refused until the plugin is reloaded
+pub fn load_yaml<T: DeserializeOwned + Default>(file: &str) -> T {
+    let path = data_path(file);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_yaml::from_str(&content).unwrap_or_else(|err| {
+            log::error!(
+                "Could not parse {}, using defaults and leaving the file unchanged: {}",
+                path.display(),
+                err
+            );
+            if let Ok(mut unreadable) = UNREADABLE_FILES.lock() {
+                unreadable.insert(file.to_string());
+            }
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+// Save a value as a yml file in the data folder
+pub fn save_yaml<T: Serialize>(file: &str, value: &T) {
+    let path = data_path(file);
+    if UNREADABLE_FILES.lock().is_ok_and(|unreadable| unreadable.contains(file)) {
+        log::warn!("Not saving {} because it could not be parsed, fix it and reload", path.display());
+        return;
+    }
+    match serde_yaml::to_string(value) {
+        Ok(content) => {
+            if let Err(err) = fs::write(&path, content) {
+                log::error!("Could not write {}: {}", path.display(), err);
+            }
+        }
+        Err(err) => log::error!("Could not serialize {}: {}", path.display(), err),
+    }
+}
@@ -0,0 +1,136 @@
+use pumpkin_util::text::color::NamedColor;
+use pumpkin_util::text::TextComponent;
+
+// Legacy & color and format codes, used by nicknames and chat
+
+const COLOR_CODES: &str = "0123456789abcdef";
+// Obfuscated, bold, strikethrough, underlined, italic and reset
+const FORMAT_CODES: &str = "klmnor";
+
+fn named_color(code: char) -> Option<NamedColor> {
+    Some(match code {
+        '0' => NamedColor::Black,
+        '1' => NamedColor::DarkBlue,
+        '2' => NamedColor::DarkGreen,
+        '3' => NamedColor::DarkAqua,
+        '4' => NamedColor::DarkRed,
+        '5' => NamedColor::DarkPurple,
+        '6' => NamedColor::Gold,
+        '7' => NamedColor::Gray,
+        '8' => NamedColor::DarkGray,
+        '9' => NamedColor::Blue,
+        'a' => NamedColor::Green,
+        'b' => NamedColor::Aqua,
+        'c' => NamedColor::Red,
+        'd' => NamedColor::LightPurple,
+        'e' => NamedColor::Yellow,
+        'f' => NamedColor::White,
+        _ => return None,
+    })
+}
+
+// Helper function returning the lowercase code when c and next form a valid & code
+fn is_code(c: char, next: Option<char>) -> Option<char> {
+    let next = next?.to_ascii_lowercase();
+    if (c == '&' || c == '§') && (COLOR_CODES.contains(next) || FORMAT_CODES.contains(next)) {
+        Some(next)
+    } else {
+        None
+    }
+}
+
+// Remove the codes the player isn't allowed to use, keeping the others
+pub fn filter_codes(text: &str, allow_color: bool, allow_format: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(code) = is_code(c, chars.peek().copied()) {
+            let allowed = if COLOR_CODES.contains(code) { allow_color } else { allow_format };
+            let code_char = chars.next().unwrap_or(code);
+            if allowed {
+                result.push(c);
+                result.push(code_char);
+            }
+            continue;
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+// Text without any code, e.g. to compare nicknames
+pub fn strip_codes(text: &str) -> String {
+    filter_codes(text, false, false)
+}
+
+#[derive(Clone, Copy, Default)]
+struct Style {
+    color: Option<NamedColor>,
+    obfuscated: bool,
+    bold: bool,
+    strikethrough: bool,
+    underlined: bool,
+    italic: bool,
+}
+
+fn styled(text: String, style: Style) -> TextComponent {
+    let mut component = TextComponent::text(text);
+    if let Some(color) = style.color {
+        component = component.color_named(color);
+    }
+    if style.obfuscated {
+        component = component.obfuscated();
+    }
+    if style.bold {
+        component = component.bold();
+    }
+    if style.strikethrough {
+        component = component.strikethrough();
+    }
+    if style.underlined {
+        component = component.underlined();
+    }
+    if style.italic {
+        component = component.italic();
+    }
+    component
+}
+
+// Turn text with & codes into a styled component. A color code resets the formatting
+// like in vanilla
+pub fn parse_codes(text: &str) -> TextComponent {
+    let mut root = TextComponent::text("");
+    let mut style = Style::default();
+    let mut segment = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let Some(code) = is_code(c, chars.peek().copied()) else {
+            segment.push(c);
+            continue;
+        };
+        chars.next();
+
+        if !segment.is_empty() {
+            root = root.add_child(styled(std::mem::take(&mut segment), style));
+        }
+        match code {
+            'k' => style.obfuscated = true,
+            'l' => style.bold = true,
+            'm' => style.strikethrough = true,
+            'n' => style.underlined = true,
+            'o' => style.italic = true,
+            'r' => style = Style::default(),
+            color => {
+                style = Style { color: named_color(color), ..Style::default() };
+            }
+        }
+    }
+
+    if !segment.is_empty() {
+        root = root.add_child(styled(segment, style));
+    }
+    root
+}